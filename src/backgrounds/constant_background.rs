@@ -0,0 +1,29 @@
+// backgrounds/constant_background.rs - Backgrounds of one colour.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Vector };
+
+use super::Background;
+
+// The same light from every direction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstantBackground {
+    color: Color,
+}
+
+impl ConstantBackground {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for ConstantBackground {
+    fn radiance(&self, _direction: Vector) -> Color {
+        self.color
+    }
+
+    fn is_light(&self) -> bool {
+        self.color != Color::black()
+    }
+}
@@ -0,0 +1,162 @@
+// backgrounds/distribution.rs - Piecewise-constant distributions.
+// Written by quadfault
+// 10/18/26
+
+// A distribution over [0, 1) split into equal pieces, each with a constant
+// density in proportion to its weight.
+pub struct Distribution1D {
+    // cdf[i] is the chance of a sample before piece i, so it runs from 0 to
+    // 1 and has one more entry than there are pieces.
+    cdf: Vec<f64>,
+    total: f64,
+}
+
+impl Distribution1D {
+    // Negative weights count as zero. If they're all zero, every piece is
+    // as likely as any other.
+    pub fn new(weights: &[f64]) -> Self {
+        assert!(!weights.is_empty(), "a distribution needs some pieces");
+
+        let total: f64 = weights.iter().map(|w| w.max(0.0)).sum();
+        let n = weights.len() as f64;
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut sum = 0.0;
+        cdf.push(0.0);
+        for (i, &w) in weights.iter().enumerate() {
+            sum += w.max(0.0);
+            // With no weight at all, every piece is as likely as any other.
+            let fraction = if total > 0.0 {
+                sum / total
+            } else {
+                (i + 1) as f64 / n
+            };
+            cdf.push(fraction);
+        }
+
+        Self { cdf, total }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cdf.len() - 1
+    }
+
+    // The sum of the weights.
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    // Turns `u` in [0, 1) into a sample in [0, 1) and the piece it's in,
+    // never picking a piece with no weight.
+    pub fn sample(&self, u: f64) -> (f64, usize) {
+        let i = self.cdf[1..]
+            .partition_point(|&c| c <= u)
+            .min(self.len() - 1);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let offset = if width > 0.0 {
+            ((u - self.cdf[i]) / width).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        (((i as f64 + offset) / self.len() as f64).min(1.0 - 1e-12), i)
+    }
+
+    // The density of samples in piece i.
+    pub fn pdf(&self, i: usize) -> f64 {
+        (self.cdf[i + 1] - self.cdf[i]) * self.len() as f64
+    }
+}
+
+// A distribution over the unit square, split into a grid of pieces. It
+// picks a row by the rows' total weights, then a piece within the row.
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    // The weights are row by row, `width` to a row.
+    pub fn new(weights: &[f64], width: usize) -> Self {
+        let rows: Vec<_> = weights.chunks(width)
+            .map(Distribution1D::new)
+            .collect();
+        let totals: Vec<_> = rows.iter().map(|row| row.total()).collect();
+
+        Self {
+            marginal: Distribution1D::new(&totals),
+            rows,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marginal.total() <= 0.0
+    }
+
+    // A point (x, y) in the unit square, with y picking the row, and its
+    // density.
+    pub fn sample(&self, u: (f64, f64)) -> ((f64, f64), f64) {
+        let (y, row) = self.marginal.sample(u.1);
+        let (x, column) = self.rows[row].sample(u.0);
+
+        ((x, y), self.marginal.pdf(row) * self.rows[row].pdf(column))
+    }
+
+    pub fn pdf(&self, (x, y): (f64, f64)) -> f64 {
+        let row = piece(y, self.rows.len());
+        let column = piece(x, self.rows[row].len());
+
+        self.marginal.pdf(row) * self.rows[row].pdf(column)
+    }
+}
+
+// Which of n equal pieces of [0, 1) x falls in.
+pub fn piece(x: f64, n: usize) -> usize {
+    ((x * n as f64) as usize).min(n - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_follow_the_weights() {
+        let d = Distribution1D::new(&[1.0, 0.0, 3.0]);
+
+        assert_eq!(d.pdf(0), 0.75);
+        assert_eq!(d.pdf(1), 0.0);
+        assert_eq!(d.pdf(2), 2.25);
+        assert_eq!(d.sample(0.0), (0.0, 0));
+        assert_eq!(d.sample(0.125), (1.0 / 6.0, 0));
+        // Past the first quarter, samples skip the empty middle piece.
+        let (x, i) = d.sample(0.25);
+        assert_eq!(i, 2);
+        assert!((x - 2.0 / 3.0).abs() < 1e-12);
+        assert!(d.sample(0.999999).0 < 1.0);
+
+        let even = Distribution1D::new(&[0.0, 0.0]);
+        assert_eq!(even.pdf(1), 1.0);
+    }
+
+    #[test]
+    fn densities_integrate_to_one() {
+        let weights: Vec<f64> = (0..12).map(|i| (i % 5) as f64).collect();
+        let d = Distribution2D::new(&weights, 4);
+        let n = 120;
+        let mut total = 0.0;
+
+        for i in 0..n {
+            for j in 0..n {
+                let x = (j as f64 + 0.5) / n as f64;
+                let y = (i as f64 + 0.5) / n as f64;
+                total += d.pdf((x, y));
+            }
+        }
+        assert!((total / (n * n) as f64 - 1.0).abs() < 1e-9);
+
+        for &u in &[(0.1, 0.2), (0.5, 0.5), (0.9, 0.99)] {
+            let (point, pdf) = d.sample(u);
+            assert!(pdf > 0.0);
+            assert_eq!(pdf, d.pdf(point));
+        }
+    }
+}
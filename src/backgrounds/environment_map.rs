@@ -0,0 +1,197 @@
+// backgrounds/environment_map.rs - Image-based lighting.
+// Written by quadfault
+// 10/18/26
+
+use std::f64::consts::PI;
+
+use crate::image::Image;
+use crate::math::{ Color, Vector };
+
+use super::Background;
+use super::distribution::{ Distribution2D, piece };
+
+// An equirectangular image of the light all around the scene, as the
+// equirectangular camera would render it: longitude across the image with
+// -Z in the middle and +X three quarters of the way across, and latitude up
+// it with straight up at the top. Directions are sampled in proportion to
+// how bright the image is there, so small, bright lights in it (like the
+// sun) are found without much noise.
+pub struct EnvironmentMap {
+    image: Image,
+    // The cosine and sine of how far the map is turned.
+    rotation: (f64, f64),
+    intensity: f32,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    // The map is turned `rotation` degrees anticlockwise about +Y (as seen
+    // from above), and its pixels scaled by `intensity`.
+    pub fn new(image: Image, rotation: f64, intensity: f32) -> Self {
+        let (width, height) = (image.width(), image.height());
+
+        // Rows near the poles cover less of the sphere, and count for less.
+        let weights: Vec<f64> = image.pixels()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let theta = ((i / width) as f64 + 0.5) / height as f64 * PI;
+                f64::from(c.luminance()) * theta.sin()
+            })
+            .collect();
+        let radians = rotation.to_radians();
+
+        Self {
+            distribution: Distribution2D::new(&weights, width),
+            image,
+            rotation: (radians.cos(), radians.sin()),
+            intensity,
+        }
+    }
+
+    // Where a unit vector falls in the image, from (0, 0) at the top left
+    // to (1, 1) at the bottom right.
+    fn image_point(&self, direction: Vector) -> (f64, f64) {
+        let (cos, sin) = self.rotation;
+        let x = cos * direction.x - sin * direction.z;
+        let z = sin * direction.x + cos * direction.z;
+        let phi = x.atan2(-z);
+        let theta = direction.y.clamp(-1.0, 1.0).acos();
+
+        ((0.5 + phi / (2.0 * PI)).rem_euclid(1.0), theta / PI)
+    }
+
+    fn direction(&self, (s, t): (f64, f64)) -> Vector {
+        let phi = (s - 0.5) * 2.0 * PI;
+        let theta = t * PI;
+        let x = theta.sin() * phi.sin();
+        let z = -theta.sin() * phi.cos();
+        let (cos, sin) = self.rotation;
+
+        Vector::new(cos * x + sin * z, theta.cos(), -sin * x + cos * z)
+    }
+
+    // The density by solid angle of a direction at image point (s, t),
+    // from the density over the image.
+    fn solid_angle_pdf(&self, pdf: f64, t: f64) -> f64 {
+        let sin_theta = (t * PI).sin();
+
+        if sin_theta > 0.0 { pdf / (2.0 * PI * PI * sin_theta) } else { 0.0 }
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn radiance(&self, direction: Vector) -> Color {
+        let (s, t) = self.image_point(direction.hat());
+        let x = piece(s, self.image.width());
+        let y = piece(t, self.image.height());
+
+        self.image.get(x, y) * self.intensity
+    }
+
+    fn is_light(&self) -> bool {
+        self.intensity > 0.0 && !self.distribution.is_empty()
+    }
+
+    fn sample(&self, u: (f64, f64)) -> Option<(Vector, f64)> {
+        let (point, pdf) = self.distribution.sample(u);
+        let pdf = self.solid_angle_pdf(pdf, point.1);
+
+        if pdf > 0.0 {
+            Some((self.direction(point), pdf))
+        } else {
+            None
+        }
+    }
+
+    fn pdf(&self, direction: Vector) -> f64 {
+        let point = self.image_point(direction);
+
+        self.solid_angle_pdf(self.distribution.pdf(point), point.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A dim map with one bright pixel a little above the horizon.
+    fn map(rotation: f64) -> EnvironmentMap {
+        let (width, height) = (16, 8);
+        let mut pixels = vec![Color::new(0.1, 0.1, 0.1); width * height];
+        pixels[3 * width + 12] = Color::new(500.0, 400.0, 300.0);
+
+        EnvironmentMap::new(Image::new(width, height, pixels), rotation, 2.0)
+    }
+
+    #[test]
+    fn directions_round_trip() {
+        for &rotation in &[0.0, 90.0, -30.0] {
+            let map = map(rotation);
+
+            for &point in &[(0.5, 0.5), (0.1, 0.3), (0.8, 0.95)] {
+                let back = map.image_point(map.direction(point));
+                assert!((back.0 - point.0).abs() < 1e-9);
+                assert!((back.1 - point.1).abs() < 1e-9);
+            }
+        }
+
+        // Unturned, -Z is in the middle and +X three quarters across.
+        let map = map(0.0);
+        let middle = map.image_point(Vector::new(0.0, 0.0, -1.0));
+        assert!((middle.0 - 0.5).abs() < 1e-12);
+        let right = map.image_point(Vector::new(1.0, 0.0, 0.0));
+        assert!((right.0 - 0.75).abs() < 1e-12);
+
+        // Turned a quarter anticlockwise, -Z is where +X was.
+        let turned = EnvironmentMap::new(
+            Image::new(1, 1, vec![Color::black()]), 90.0, 1.0,
+        );
+        let point = turned.image_point(Vector::new(-1.0, 0.0, 0.0));
+        assert!((point.0 - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn samples_find_the_bright_pixel() {
+        let map = map(45.0);
+        assert!(map.is_light());
+        assert_eq!(map.radiance(Vector::new(0.0, -1.0, 0.0)),
+                   Color::new(0.2, 0.2, 0.2));
+
+        let n = 1000;
+        let mut bright = 0;
+        for i in 0..n {
+            let u = (
+                (i as f64 + 0.5) / n as f64,
+                ((i * 7 % n) as f64 + 0.5) / n as f64,
+            );
+            let (direction, pdf) = map.sample(u).unwrap();
+
+            assert!((direction.norm() - 1.0).abs() < 1e-9);
+            assert!((pdf - map.pdf(direction)).abs() < 1e-6 * pdf);
+            if map.radiance(direction).r > 1.0 {
+                bright += 1;
+            }
+        }
+        assert!(bright > 9 * n / 10, "only {} of {}", bright, n);
+    }
+
+    #[test]
+    fn densities_integrate_to_one() {
+        let map = map(10.0);
+        let n = 400;
+        let mut total = 0.0;
+
+        for i in 0..n {
+            let z = 1.0 - 2.0 * (i as f64 + 0.5) / n as f64;
+            let r = (1.0 - z * z).sqrt();
+            for j in 0..n {
+                let phi = 2.0 * PI * (j as f64 + 0.5) / n as f64;
+                total += map.pdf(Vector::new(r * phi.cos(), z, r * phi.sin()));
+            }
+        }
+
+        let integral = total * 4.0 * PI / (n * n) as f64;
+        assert!((integral - 1.0).abs() < 0.01, "integral {}", integral);
+    }
+}
@@ -0,0 +1,50 @@
+// backgrounds/mod.rs - What rays that escape the scene see.
+// Written by quadfault
+// 10/18/26
+
+mod constant_background;
+mod distribution;
+mod environment_map;
+mod sky;
+
+pub use self::constant_background::*;
+pub use self::environment_map::*;
+pub use self::sky::*;
+
+use std::f64::consts::PI;
+
+use crate::math::{ Color, Vector };
+
+// The light arriving from infinitely far away, all around the scene. It
+// lights the scene too, and is sampled like the area lights.
+pub trait Background: Send + Sync {
+    // The light seen looking along `direction`, which needn't be a unit
+    // vector.
+    fn radiance(&self, direction: Vector) -> Color;
+
+    // Whether there's any light worth sampling.
+    fn is_light(&self) -> bool {
+        true
+    }
+
+    // Picks a unit vector to look for light along, using `u`, two numbers
+    // in [0, 1), with its density by solid angle. By default every
+    // direction is as likely as any other.
+    fn sample(&self, u: (f64, f64)) -> Option<(Vector, f64)> {
+        Some((uniform_sphere(u), 1.0 / (4.0 * PI)))
+    }
+
+    // The density by solid angle with which `sample` picks `direction`, a
+    // unit vector.
+    fn pdf(&self, _direction: Vector) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
+
+fn uniform_sphere(u: (f64, f64)) -> Vector {
+    let z = 1.0 - 2.0 * u.0;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * u.1;
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
@@ -0,0 +1,36 @@
+// backgrounds/sky.rs - Sky gradients.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Vector };
+
+use super::Background;
+
+// A vertical blend from the horizon colour (looking straight down) to the
+// zenith colour (straight up.)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sky {
+    pub horizon: Color,
+    pub zenith: Color,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            horizon: Color::new(1.0, 1.0, 1.0),
+            zenith: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+impl Background for Sky {
+    fn radiance(&self, direction: Vector) -> Color {
+        let t = 0.5 * (direction.hat().y as f32 + 1.0);
+
+        Color::blend(t, self.horizon, self.zenith)
+    }
+
+    fn is_light(&self) -> bool {
+        self.horizon != Color::black() || self.zenith != Color::black()
+    }
+}
@@ -0,0 +1,147 @@
+// cameras/cubemap_camera.rs - Cameras that render the six faces of a cube.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
+
+use super::{ Camera, LookAt, image_point };
+
+// Renders what six 90 degree cameras at the eye see, looking along the
+// +X, -X, +Y, -Y, +Z and -Z axes of the view, into six square faces left
+// to right. The view's axes are right, up and backwards, so the -Z face
+// looks at the target. Looking along the X and Z axes the view's up is up;
+// the +Y face has -Z (towards the target) at its bottom, and the -Y face
+// has it at its top.
+pub struct CubemapCamera {
+    face_size: usize,
+    samples_per_pixel: usize,
+    origin: Point,
+    // Forward, right and up for each face.
+    faces: [(Vector, Vector, Vector); 6],
+}
+
+impl CubemapCamera {
+    // The image is six faces wide and one high.
+    pub fn new(face_size: usize, view: LookAt, samples_per_pixel: usize)
+        -> Self
+    {
+        let (u, v, w) = view.basis();
+
+        Self {
+            face_size,
+            samples_per_pixel,
+            origin: view.eye,
+            faces: [
+                (u, w, v),
+                (-u, -w, v),
+                (v, u, w),
+                (-v, u, -w),
+                (w, -u, v),
+                (-w, u, v),
+            ],
+        }
+    }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right.
+    fn ray(&self, s: f64, t: f64) -> Ray {
+        let s = s * 6.0;
+        let face = (s as usize).min(5);
+
+        self.face_ray(face, s - face as f64, t)
+    }
+
+    // The same, but with (s, t) within one face.
+    fn face_ray(&self, face: usize, s: f64, t: f64) -> Ray {
+        let (forward, right, up) = self.faces[face];
+
+        Ray::new(
+            self.origin,
+            forward + right * (2.0 * s - 1.0) + up * (2.0 * t - 1.0),
+        )
+    }
+}
+
+impl Camera for CubemapCamera {
+    fn get_image_width(&self) -> usize {
+        6 * self.face_size
+    }
+
+    fn get_image_height(&self) -> usize {
+        self.face_size
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    fn get_ray(&self, x: f64, y: f64, _sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        let width = self.get_image_width();
+        let (s, t) = image_point(x, y, width, self.get_image_height());
+
+        Some(self.ray(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> CubemapCamera {
+        CubemapCamera::new(
+            16,
+            LookAt::new(
+                Point::origin(),
+                Point::new(0.0, 0.0, -1.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            1,
+        )
+    }
+
+    fn close(a: Vector, b: Vector) -> bool {
+        (a.hat() - b.hat()).norm() < 1e-12
+    }
+
+    #[test]
+    fn faces_look_along_the_axes() {
+        let camera = camera();
+        let axes = [
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(0.0, 0.0, -1.0),
+        ];
+
+        assert_eq!(camera.get_image_width(), 96);
+        assert_eq!(camera.get_image_height(), 16);
+        for (i, &axis) in axes.iter().enumerate() {
+            let s = (i as f64 + 0.5) / 6.0;
+            assert!(close(camera.ray(s, 0.5).direction, axis));
+
+            // Seen from inside the cube, no face is mirrored.
+            let (forward, right, up) = camera.faces[i];
+            assert!(close(right.cross(up), -forward));
+        }
+    }
+
+    #[test]
+    fn neighbouring_faces_meet() {
+        let camera = camera();
+        let edge = |face, s, t| camera.face_ray(face, s, t).direction;
+
+        // -Z's right edge is +X's left edge, and +X's right edge is +Z's
+        // left.
+        for &t in &[0.0, 0.3, 1.0] {
+            assert!(close(edge(5, 1.0, t), edge(0, 0.0, t)));
+            assert!(close(edge(0, 1.0, t), edge(4, 0.0, t)));
+        }
+        // The bottom of +Y meets the top of -Z.
+        assert!(close(edge(2, 0.5, 0.0), edge(5, 0.5, 1.0)));
+    }
+}
@@ -0,0 +1,170 @@
+// cameras/equirectangular_camera.rs - Cameras that see in every direction.
+// Written by quadfault
+// 10/18/26
+
+use std::f64::consts::PI;
+
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
+
+use super::{ Camera, LookAt, image_point };
+
+// Maps longitude across the image and latitude up it, covering the whole
+// sphere of directions around the eye in a 360 by 180 degree image. The
+// target is in the middle, and the up vector is straight up.
+pub struct EquirectangularCamera {
+    image_width: usize,
+    image_height: usize,
+    samples_per_pixel: usize,
+    origin: Point,
+    u: Vector,
+    v: Vector,
+    w: Vector,
+    // For omni-directional stereo: how far to the right each ray starts,
+    // and where the rays converge.
+    eye_offset: f64,
+    convergence: Option<f64>,
+}
+
+impl EquirectangularCamera {
+    pub fn new(image_width: usize,
+               image_height: usize,
+               view: LookAt,
+               samples_per_pixel: usize)
+        -> Self
+    {
+        let (u, v, w) = view.basis();
+
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            origin: view.eye,
+            u,
+            v,
+            w,
+            eye_offset: 0.0,
+            convergence: None,
+        }
+    }
+
+    // Makes this one eye of an omni-directional stereo pair. Instead of
+    // starting at the eye, each ray starts `offset` to the right of it (to
+    // the left if negative), as seen looking in the ray's direction, as if
+    // the viewer turned their head to look that way. With a convergence
+    // distance, the rays are turned inwards to meet that far away.
+    pub fn with_omnidirectional_stereo(mut self,
+                                       offset: f64,
+                                       convergence: Option<f64>)
+        -> Self
+    {
+        self.eye_offset = offset;
+        self.convergence = convergence;
+        self
+    }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right.
+    fn ray(&self, s: f64, t: f64) -> Ray {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+
+        let direction = self.u * (latitude.cos() * longitude.sin())
+                      + self.v * latitude.sin()
+                      - self.w * (latitude.cos() * longitude.cos());
+        if self.eye_offset == 0.0 {
+            return Ray::new(self.origin, direction);
+        }
+
+        let right = self.u * longitude.cos() + self.w * longitude.sin();
+        let origin = self.origin + right * self.eye_offset;
+        match self.convergence {
+            Some(d) => {
+                Ray::new(origin, self.origin + direction * d - origin)
+            }
+            None => Ray::new(origin, direction),
+        }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_image_width(&self) -> usize {
+        self.image_width
+    }
+
+    fn get_image_height(&self) -> usize {
+        self.image_height
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    fn get_ray(&self, x: f64, y: f64, _sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        let (s, t) = image_point(x, y, self.image_width, self.image_height);
+
+        Some(self.ray(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::rays;
+    use super::*;
+
+    #[test]
+    fn directions() {
+        let camera = EquirectangularCamera::new(
+            200,
+            100,
+            LookAt::new(
+                Point::origin(),
+                Point::new(1.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            1,
+        );
+        let direction = |s, t| camera.ray(s, t).direction;
+        let close = |a: Vector, b: Vector| (a - b).norm() < 1e-12;
+
+        assert!(close(direction(0.5, 0.5), Vector::new(1.0, 0.0, 0.0)));
+        assert!(close(direction(0.75, 0.5), Vector::new(0.0, 0.0, 1.0)));
+        assert!(close(direction(0.0, 0.5), Vector::new(-1.0, 0.0, 0.0)));
+        assert!(close(direction(1.0, 0.5), Vector::new(-1.0, 0.0, 0.0)));
+        assert!(close(direction(0.3, 1.0), Vector::new(0.0, 1.0, 0.0)));
+        assert!(close(direction(0.9, 0.0), Vector::new(0.0, -1.0, 0.0)));
+
+        for ray in rays(&camera, 17, 63) {
+            assert!((ray.direction.norm() - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn omnidirectional_stereo() {
+        let view = LookAt::new(
+            Point::origin(),
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let centre = EquirectangularCamera::new(200, 100, view, 1);
+        let left = EquirectangularCamera::new(200, 100, view, 1)
+            .with_omnidirectional_stereo(-0.03, Some(2.0));
+
+        // Whichever way the left eye looks, it sits to the left of the
+        // centre, and its rays meet the centre's 2 units away.
+        for &s in &[0.1, 0.3, 0.5, 0.8] {
+            let ray = left.ray(s, 0.5);
+            let looking = centre.ray(s, 0.5).direction;
+            let offset = ray.origin - Point::origin();
+            assert!((offset.norm() - 0.03).abs() < 1e-12);
+            assert!(offset.dot(looking).abs() < 1e-12);
+            assert!(looking.cross(offset).y > 0.0);
+
+            let centre = Point::origin() + looking * 2.0;
+            let t = (centre - ray.origin).norm() / ray.direction.norm();
+            assert!((ray.at(t) - centre).norm() < 1e-6);
+        }
+    }
+}
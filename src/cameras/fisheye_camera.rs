@@ -0,0 +1,169 @@
+// cameras/fisheye_camera.rs - Fisheye cameras.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
+
+use super::{ Camera, LookAt, image_point };
+
+// Sees a circle of directions, up to all of them, around the target. The
+// circle fills the shorter side of the image; pixels outside it stay black.
+pub struct FisheyeCamera {
+    image_width: usize,
+    image_height: usize,
+    samples_per_pixel: usize,
+    origin: Point,
+    u: Vector,
+    v: Vector,
+    w: Vector,
+    half_fov: f64,
+    projection: FisheyeProjection,
+}
+
+// How the angle from the centre of view maps to distance from the centre
+// of the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FisheyeProjection {
+    // Distance is proportional to the angle.
+    Equidistant,
+    // Area is proportional to solid angle, like most real fisheye lenses.
+    Equisolid,
+}
+
+impl FisheyeCamera {
+    // `fov` is the angle across the image circle, in degrees, up to 360.
+    pub fn new(image_width: usize,
+               image_height: usize,
+               view: LookAt,
+               fov: f64,
+               projection: FisheyeProjection,
+               samples_per_pixel: usize)
+        -> Self
+    {
+        let (u, v, w) = view.basis();
+
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            origin: view.eye,
+            u,
+            v,
+            w,
+            half_fov: fov.to_radians() / 2.0,
+            projection,
+        }
+    }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right, or None outside the image circle.
+    fn ray(&self, s: f64, t: f64) -> Option<Ray> {
+        let (w, h) = (self.image_width as f64, self.image_height as f64);
+        let radius = w.min(h) / 2.0;
+        let x = (s - 0.5) * w / radius;
+        let y = (t - 0.5) * h / radius;
+
+        let r = x.hypot(y);
+        if r > 1.0 {
+            return None;
+        }
+
+        let theta = match self.projection {
+            FisheyeProjection::Equidistant => r * self.half_fov,
+            FisheyeProjection::Equisolid => {
+                2.0 * (r * (self.half_fov / 2.0).sin()).asin()
+            }
+        };
+        let phi = y.atan2(x);
+
+        Some(Ray::new(
+            self.origin,
+            (self.u * phi.cos() + self.v * phi.sin()) * theta.sin()
+                - self.w * theta.cos(),
+        ))
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn get_image_width(&self) -> usize {
+        self.image_width
+    }
+
+    fn get_image_height(&self) -> usize {
+        self.image_height
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    fn get_ray(&self, x: f64, y: f64, _sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        let (s, t) = image_point(x, y, self.image_width, self.image_height);
+
+        self.ray(s, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::rays;
+    use super::*;
+
+    fn camera(fov: f64, projection: FisheyeProjection) -> FisheyeCamera {
+        FisheyeCamera::new(
+            200,
+            100,
+            LookAt::new(
+                Point::origin(),
+                Point::new(0.0, 0.0, -1.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            fov,
+            projection,
+            4,
+        )
+    }
+
+    // The angle between a ray and the view direction, in degrees.
+    fn angle(ray: Ray) -> f64 {
+        let forward = Vector::new(0.0, 0.0, -1.0);
+
+        (ray.direction.hat().dot(forward)).acos().to_degrees()
+    }
+
+    #[test]
+    fn equidistant() {
+        let camera = camera(180.0, FisheyeProjection::Equidistant);
+
+        assert!(angle(camera.ray(0.5, 0.5).unwrap()) < 1e-6);
+        assert!((angle(camera.ray(0.5, 0.75).unwrap()) - 45.0).abs() < 1e-6);
+        assert!((angle(camera.ray(0.5, 1.0).unwrap()) - 90.0).abs() < 1e-6);
+
+        // Right of the centre is right in the scene.
+        let right = camera.ray(0.625, 0.5).unwrap().direction;
+        assert!(right.x > 0.0 && right.y.abs() < 1e-12);
+    }
+
+    #[test]
+    fn equisolid() {
+        let camera = camera(360.0, FisheyeProjection::Equisolid);
+
+        // Half the area of the circle sees half the sphere, and a quarter
+        // sees a quarter, out to 60 degrees.
+        let t = 0.5 + 0.5_f64.sqrt() / 2.0;
+        assert!((angle(camera.ray(0.5, t).unwrap()) - 90.0).abs() < 1e-6);
+        assert!((angle(camera.ray(0.5, 0.75).unwrap()) - 60.0).abs() < 1e-6);
+        assert!((angle(camera.ray(0.25, 0.5).unwrap()) - 180.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn outside_the_circle_is_black() {
+        let camera = camera(180.0, FisheyeProjection::Equidistant);
+
+        assert_eq!(rays(&camera, 0, 0).len(), 0);
+        assert_eq!(rays(&camera, 100, 50).len(), 4);
+    }
+}
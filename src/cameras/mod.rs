@@ -2,21 +2,87 @@
 // Written by quadfault
 // 10/19/18
 
-//mod bad_camera;
+mod cubemap_camera;
+mod equirectangular_camera;
+mod fisheye_camera;
 mod orthographic_camera;
-//mod pinhole_camera;
+mod perspective_camera;
+mod stereo_camera;
+mod thin_lens_camera;
 
-//pub use self::bad_camera::*;
+pub use self::cubemap_camera::*;
+pub use self::equirectangular_camera::*;
+pub use self::fisheye_camera::*;
 pub use self::orthographic_camera::*;
-//pub use self::pinhole_camera::*;
+pub use self::perspective_camera::*;
+pub use self::stereo_camera::*;
+pub use self::thin_lens_camera::*;
 
-use crate::math::Ray;
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
 
-pub trait Camera {
+pub trait Camera: Send + Sync {
     fn get_image_width(&self) -> usize;
     fn get_image_height(&self) -> usize;
     fn get_samples_per_pixel(&self) -> usize;
 
-    fn rays<'a>(&'a self, x: usize, y: usize)
-        -> Box<dyn Iterator<Item=Ray> + 'a>;
+    // A ray through the point (x, y) on the film, measured in pixels from the
+    // bottom left corner, so that pixel (i, j) covers [i, i + 1) x [j, j + 1).
+    // Cameras with more to choose, like a point on a lens, use the sampler's
+    // next few dimensions. None if the camera sees nothing at that point,
+    // like outside a fisheye's image circle.
+    fn get_ray(&self, x: f64, y: f64, sampler: &mut dyn Sampler)
+        -> Option<Ray>;
+}
+
+// Where a camera is and which way it faces: from `eye` towards `target`,
+// turned about that line so that `up` points up in the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LookAt {
+    pub eye: Point,
+    pub target: Point,
+    pub up: Vector,
+}
+
+impl LookAt {
+    pub fn new(eye: Point, target: Point, up: Vector) -> Self {
+        Self { eye, target, up }
+    }
+
+    // An orthonormal basis for the camera: right, up, and backwards (away
+    // from the target.)
+    pub fn basis(&self) -> (Vector, Vector, Vector) {
+        let w = (self.eye - self.target).hat();
+        let u = self.up.cross(w).hat();
+        let v = w.cross(u);
+
+        (u, v, w)
+    }
+}
+
+// A point on the film of a width by height image, scaled so that the whole
+// image runs from (0, 0) at the bottom left to (1, 1) at the top right.
+fn image_point(x: f64, y: f64, width: usize, height: usize) -> (f64, f64) {
+    (x / width as f64, y / height as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::samplers::IndependentSampler;
+
+    use super::*;
+
+    // Every ray the camera takes through a pixel.
+    pub fn rays(camera: &dyn Camera, x: usize, y: usize) -> Vec<Ray> {
+        let mut sampler = IndependentSampler::new();
+        let count = camera.get_samples_per_pixel();
+
+        (0..count)
+            .filter_map(|i| {
+                sampler.start_pixel_sample(x, y, i, count);
+                let (dx, dy) = sampler.get_2d();
+                camera.get_ray(x as f64 + dx, y as f64 + dy, &mut sampler)
+            })
+            .collect()
+    }
 }
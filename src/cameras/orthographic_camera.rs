@@ -2,24 +2,27 @@
 // Written by quadfault
 // 10/26/18
 
-use rand::prelude::*;
-
 use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
 
-use super::Camera;
+use super::{ Camera, LookAt, image_point };
 
+// Every ray runs parallel to the view direction, from a point on a view
+// plane through the eye, so things keep their size however far away they
+// are.
 pub struct OrthographicCamera {
     image_width: usize,
     image_height: usize,
     samples_per_pixel: usize,
-    pixel_width: f64,
-    pixel_height: f64,
-    view_plane_half_width: f64,
-    view_plane_half_height: f64,
+    lower_left_corner: Point,
+    horizontal: Vector,
+    vertical: Vector,
     direction: Vector,
 }
 
 impl OrthographicCamera {
+    // Looks down -Z from the z = 0 plane, centred on the origin, with the
+    // view plane as tall as the image's aspect ratio needs.
     pub fn new(image_width: usize,
                image_height: usize,
                view_plane_width: f64,
@@ -28,20 +31,55 @@ impl OrthographicCamera {
     {
         let view_plane_height = view_plane_width
             * (image_height as f64 / image_width as f64);
-        let pixel_width = view_plane_width / image_width as f64;
-        let pixel_height = view_plane_height / image_height as f64;
+
+        Self::with_view(
+            image_width,
+            image_height,
+            LookAt::new(
+                Point::origin(),
+                Point::new(0.0, 0.0, -1.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            view_plane_width,
+            view_plane_height,
+            samples_per_pixel,
+        )
+    }
+
+    // The view plane is centred on the eye, facing the target.
+    pub fn with_view(image_width: usize,
+                     image_height: usize,
+                     view: LookAt,
+                     view_plane_width: f64,
+                     view_plane_height: f64,
+                     samples_per_pixel: usize)
+        -> Self
+    {
+        let (u, v, w) = view.basis();
+        let horizontal = u * view_plane_width;
+        let vertical = v * view_plane_height;
 
         Self {
             image_width,
             image_height,
             samples_per_pixel,
-            pixel_width,
-            pixel_height,
-            view_plane_half_width: view_plane_width / 2.0,
-            view_plane_half_height: view_plane_height / 2.0,
-            direction: Vector::new(0.0, 0.0, -1.0),
+            lower_left_corner: view.eye - horizontal / 2.0 - vertical / 2.0,
+            horizontal,
+            vertical,
+            direction: -w,
         }
     }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right.
+    fn ray(&self, s: f64, t: f64) -> Ray {
+        Ray::new(
+            self.lower_left_corner
+                + self.horizontal * s
+                + self.vertical * t,
+            self.direction,
+        )
+    }
 }
 
 impl Camera for OrthographicCamera {
@@ -57,55 +95,59 @@ impl Camera for OrthographicCamera {
         self.samples_per_pixel
     }
 
-    fn rays<'a>(&'a self, x: usize, y: usize) 
-        -> Box<dyn Iterator<Item=Ray> + 'a>
+    fn get_ray(&self, x: f64, y: f64, _sampler: &mut dyn Sampler)
+        -> Option<Ray>
     {
-        Box::new(Rays {
-            x: x as f64,
-            y: y as f64,
-            rays_left: self.samples_per_pixel,
-            rng: thread_rng(),
-            camera: self,
-        })
+        let (s, t) = image_point(x, y, self.image_width, self.image_height);
+
+        Some(self.ray(s, t))
     }
 }
 
-struct Rays<'a> {
-    x: f64,
-    y: f64,
-    rays_left: usize,
-    rng: ThreadRng,
-    camera: &'a OrthographicCamera,
-}
+#[cfg(test)]
+mod tests {
+    use super::super::tests::rays;
+    use super::*;
 
-impl<'a> Iterator for Rays<'a> {
-    type Item = Ray;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.rays_left == 0 {
-            None
-        } else {
-            self.rays_left -= 1;
-
-            let horizontal_sample_offset =
-                self.rng.gen::<f64>() * self.camera.pixel_width;
-            let vertical_sample_offset =
-                self.rng.gen::<f64>() * self.camera.pixel_height;
-
-            let origin = Point::new(
-                self.x * self.camera.pixel_width
-                    - self.camera.view_plane_half_width
-                    + horizontal_sample_offset,
-                self.y * self.camera.pixel_height
-                    - self.camera.view_plane_half_height
-                    + vertical_sample_offset,
-                0.0,
-            );
-
-            Some(Ray {
-                origin,
-                direction: self.camera.direction
-            })
+    #[test]
+    fn default_view() {
+        let camera = OrthographicCamera::new(200, 100, 4.0, 1);
+
+        let ray = camera.ray(0.0, 0.0);
+        assert_eq!(ray.origin, Point::new(-2.0, -1.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(camera.ray(1.0, 1.0).origin, Point::new(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn isometric_view() {
+        let camera = OrthographicCamera::with_view(
+            100,
+            100,
+            LookAt::new(
+                Point::new(5.0, 5.0, 5.0),
+                Point::origin(),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            3.0,
+            2.0,
+            8,
+        );
+
+        let expected = Vector::new(-1.0, -1.0, -1.0).hat();
+        for ray in rays(&camera, 10, 90) {
+            assert!((ray.direction - expected).norm() < 1e-12);
         }
+
+        // The centre of the image is the eye, the right edge is level, and
+        // the view plane is the size asked for.
+        let centre = camera.ray(0.5, 0.5).origin;
+        assert!((centre - Point::new(5.0, 5.0, 5.0)).norm() < 1e-12);
+        let across = camera.ray(1.0, 0.5).origin - camera.ray(0.0, 0.5).origin;
+        assert!((across.norm() - 3.0).abs() < 1e-12);
+        assert!(across.y.abs() < 1e-12);
+        let up = camera.ray(0.5, 1.0).origin - camera.ray(0.5, 0.0).origin;
+        assert!((up.norm() - 2.0).abs() < 1e-12);
+        assert!(up.y > 0.0);
     }
 }
@@ -0,0 +1,162 @@
+// cameras/perspective_camera.rs - Perspective-projecting cameras.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
+
+use super::{ Camera, LookAt, image_point };
+
+// A pinhole camera. Every ray starts at the eye and passes through a point
+// on a view plane one unit in front of it, whose size is set by the
+// vertical field of view and the aspect ratio (width over height.)
+pub struct PerspectiveCamera {
+    image_width: usize,
+    image_height: usize,
+    samples_per_pixel: usize,
+    origin: Point,
+    lower_left_corner: Point,
+    horizontal: Vector,
+    vertical: Vector,
+}
+
+impl PerspectiveCamera {
+    // `vertical_fov` is in degrees.
+    pub fn new(image_width: usize,
+               image_height: usize,
+               view: LookAt,
+               vertical_fov: f64,
+               aspect_ratio: f64,
+               samples_per_pixel: usize)
+        -> Self
+    {
+        let half_height = (vertical_fov.to_radians() / 2.0).tan();
+        let half_width = aspect_ratio * half_height;
+        let (u, v, w) = view.basis();
+
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            origin: view.eye,
+            lower_left_corner: view.eye - u * half_width - v * half_height - w,
+            horizontal: u * (2.0 * half_width),
+            vertical: v * (2.0 * half_height),
+        }
+    }
+
+    // Moves the eye by `offset` (as for one of a stereo pair) without
+    // turning it. With a convergence distance, the view is also reframed
+    // so that things that far away stay put in the image; without one,
+    // nothing is reframed and the view is just shifted.
+    pub fn with_eye_offset(self, offset: Vector, convergence: Option<f64>)
+        -> Self
+    {
+        let origin = self.origin + offset;
+
+        match convergence {
+            Some(d) => Self {
+                origin,
+                lower_left_corner: self.origin
+                    + (self.lower_left_corner - self.origin) * d,
+                horizontal: self.horizontal * d,
+                vertical: self.vertical * d,
+                ..self
+            },
+            None => Self {
+                origin,
+                lower_left_corner: self.lower_left_corner + offset,
+                ..self
+            },
+        }
+    }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right.
+    fn ray(&self, s: f64, t: f64) -> Ray {
+        Ray::new(
+            self.origin,
+            self.lower_left_corner
+                + self.horizontal * s
+                + self.vertical * t
+                - self.origin,
+        )
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_image_width(&self) -> usize {
+        self.image_width
+    }
+
+    fn get_image_height(&self) -> usize {
+        self.image_height
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    fn get_ray(&self, x: f64, y: f64, _sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        let (s, t) = image_point(x, y, self.image_width, self.image_height);
+
+        Some(self.ray(s, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::rays;
+    use super::*;
+
+    fn camera() -> PerspectiveCamera {
+        PerspectiveCamera::new(
+            200,
+            100,
+            LookAt::new(
+                Point::new(1.0, 2.0, 3.0),
+                Point::new(1.0, 2.0, -7.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            90.0,
+            2.0,
+            4,
+        )
+    }
+
+    #[test]
+    fn centre_looks_at_target() {
+        let direction = camera().ray(0.5, 0.5).direction.hat();
+
+        assert!((direction - Vector::new(0.0, 0.0, -1.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn field_of_view() {
+        let camera = camera();
+
+        // 45 degrees either side vertically, and twice as wide.
+        let top = camera.ray(0.5, 1.0).direction;
+        assert!((top.y / -top.z - 1.0).abs() < 1e-12);
+        let right = camera.ray(1.0, 0.5).direction;
+        assert!((right.x / -right.z - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rays_stay_in_their_pixel() {
+        let camera = camera();
+
+        for ray in rays(&camera, 150, 25) {
+            assert_eq!(ray.origin, Point::new(1.0, 2.0, 3.0));
+
+            let d = ray.direction;
+            let s = (d.x / -d.z / 2.0 + 1.0) / 2.0;
+            let t = (d.y / -d.z + 1.0) / 2.0;
+            assert!((0.75 - 1e-9..=0.755 + 1e-9).contains(&s));
+            assert!((0.25 - 1e-9..=0.26 + 1e-9).contains(&t));
+        }
+        assert_eq!(rays(&camera, 0, 0).len(), 4);
+    }
+}
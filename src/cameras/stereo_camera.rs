@@ -0,0 +1,252 @@
+// cameras/stereo_camera.rs - Stereo pairs of cameras, for VR.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::Ray;
+use crate::samplers::Sampler;
+
+use super::{ Camera, EquirectangularCamera, LookAt, PerspectiveCamera };
+
+// Renders a left and a right eye into one image.
+pub struct StereoCamera {
+    eye_width: usize,
+    eye_height: usize,
+    layout: StereoLayout,
+    left: Box<dyn Camera>,
+    right: Box<dyn Camera>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stereo {
+    // The interpupillary distance: how far apart the eyes are, in scene
+    // units.
+    pub ipd: f64,
+    // How far away the eyes' views meet, so that things at that distance
+    // appear at the depth of the screen. None keeps the eyes parallel, as
+    // if converged at infinity.
+    pub convergence: Option<f64>,
+    pub layout: StereoLayout,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoLayout {
+    // Left eye on the left.
+    SideBySide,
+    // Left eye on top.
+    OverUnder,
+}
+
+impl StereoLayout {
+    // The size of each eye's image within an image of the given size.
+    pub fn eye_size(&self, width: usize, height: usize) -> (usize, usize) {
+        match *self {
+            StereoLayout::SideBySide => (width / 2, height),
+            StereoLayout::OverUnder => (width, height / 2),
+        }
+    }
+}
+
+impl StereoCamera {
+    // The eyes must make images of the same size.
+    pub fn new(left: Box<dyn Camera>,
+               right: Box<dyn Camera>,
+               layout: StereoLayout)
+        -> Self
+    {
+        let eye_width = left.get_image_width();
+        let eye_height = left.get_image_height();
+        assert_eq!(
+            (eye_width, eye_height),
+            (right.get_image_width(), right.get_image_height()),
+            "stereo eyes must have the same image size",
+        );
+
+        Self { eye_width, eye_height, layout, left, right }
+    }
+
+    // A pair of perspective cameras either side of the view's eye. They
+    // stay parallel, and converge by reframing their views rather than by
+    // turning inwards, which would skew the images vertically.
+    pub fn perspective(eye_width: usize,
+                       eye_height: usize,
+                       view: LookAt,
+                       vertical_fov: f64,
+                       aspect_ratio: f64,
+                       stereo: Stereo,
+                       samples_per_pixel: usize)
+        -> Self
+    {
+        let (u, _, _) = view.basis();
+        let eye = |side: f64| {
+            let offset = u * (side * stereo.ipd / 2.0);
+
+            Box::new(PerspectiveCamera::new(
+                eye_width,
+                eye_height,
+                view,
+                vertical_fov,
+                aspect_ratio,
+                samples_per_pixel,
+            ).with_eye_offset(offset, stereo.convergence))
+        };
+
+        Self::new(eye(-1.0), eye(1.0), stereo.layout)
+    }
+
+    // Omni-directional stereo: a pair of equirectangular panoramas with
+    // the eyes either side of the centre whichever way they look.
+    pub fn equirectangular(eye_width: usize,
+                           eye_height: usize,
+                           view: LookAt,
+                           stereo: Stereo,
+                           samples_per_pixel: usize)
+        -> Self
+    {
+        let eye = |side: f64| {
+            Box::new(EquirectangularCamera::new(
+                eye_width,
+                eye_height,
+                view,
+                samples_per_pixel,
+            ).with_omnidirectional_stereo(
+                side * stereo.ipd / 2.0,
+                stereo.convergence,
+            ))
+        };
+
+        Self::new(eye(-1.0), eye(1.0), stereo.layout)
+    }
+}
+
+impl Camera for StereoCamera {
+    fn get_image_width(&self) -> usize {
+        match self.layout {
+            StereoLayout::SideBySide => 2 * self.eye_width,
+            StereoLayout::OverUnder => self.eye_width,
+        }
+    }
+
+    fn get_image_height(&self) -> usize {
+        match self.layout {
+            StereoLayout::SideBySide => self.eye_height,
+            StereoLayout::OverUnder => 2 * self.eye_height,
+        }
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.left.get_samples_per_pixel()
+    }
+
+    fn get_ray(&self, x: f64, y: f64, sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        // Remember that y counts up from the bottom.
+        match self.layout {
+            StereoLayout::SideBySide if x < self.eye_width as f64 => {
+                self.left.get_ray(x, y, sampler)
+            }
+            StereoLayout::SideBySide => {
+                self.right.get_ray(x - self.eye_width as f64, y, sampler)
+            }
+            StereoLayout::OverUnder if y >= self.eye_height as f64 => {
+                self.left.get_ray(x, y - self.eye_height as f64, sampler)
+            }
+            StereoLayout::OverUnder => self.right.get_ray(x, y, sampler),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{ Point, Vector };
+
+    use super::super::tests::rays;
+    use super::*;
+
+    fn view() -> LookAt {
+        LookAt::new(
+            Point::origin(),
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 1.0, 0.0),
+        )
+    }
+
+    fn stereo(convergence: Option<f64>, layout: StereoLayout) -> Stereo {
+        Stereo { ipd: 0.064, convergence, layout }
+    }
+
+    // Where a ray crosses the plane z = `z`.
+    fn crossing(ray: &Ray, z: f64) -> Point {
+        ray.at((z - ray.origin.z) / ray.direction.z)
+    }
+
+    #[test]
+    fn side_by_side() {
+        let camera = StereoCamera::perspective(
+            40,
+            30,
+            view(),
+            60.0,
+            4.0 / 3.0,
+            stereo(Some(3.0), StereoLayout::SideBySide),
+            2,
+        );
+        assert_eq!(camera.get_image_width(), 80);
+        assert_eq!(camera.get_image_height(), 30);
+
+        // The left half sees from the left eye and the right half from the
+        // right, and the same pixel in each sees the same point at the
+        // convergence distance.
+        let half_width = 3.0 * (4.0 / 3.0) * 30_f64.to_radians().tan();
+        let pixel_width = 2.0 * half_width / 40.0;
+        for (left, right) in rays(&camera, 7, 11).iter()
+            .zip(&rays(&camera, 47, 11))
+        {
+            assert!((left.origin.x + 0.032).abs() < 1e-12);
+            assert!((right.origin.x - 0.032).abs() < 1e-12);
+
+            let l = crossing(left, -3.0);
+            let r = crossing(right, -3.0);
+            assert!((l - r).norm() < 2.0 * pixel_width);
+        }
+    }
+
+    #[test]
+    fn parallel_eyes() {
+        let camera = StereoCamera::perspective(
+            10,
+            10,
+            view(),
+            90.0,
+            1.0,
+            stereo(None, StereoLayout::OverUnder),
+            1,
+        );
+        assert_eq!(camera.get_image_width(), 10);
+        assert_eq!(camera.get_image_height(), 20);
+
+        // Left eye on top, looking the same way as the right below it.
+        let left = rays(&*camera.left, 3, 4).remove(0);
+        let top = rays(&camera, 3, 14).remove(0);
+        let bottom = rays(&camera, 3, 4).remove(0);
+        assert_eq!(top.origin, left.origin);
+        assert!((bottom.origin.x - top.origin.x - 0.064).abs() < 1e-12);
+    }
+
+    #[test]
+    fn omnidirectional() {
+        let camera = StereoCamera::equirectangular(
+            64,
+            32,
+            view(),
+            stereo(None, StereoLayout::OverUnder),
+            1,
+        );
+
+        assert_eq!(camera.get_image_width(), 64);
+        assert_eq!(camera.get_image_height(), 64);
+        // Looking backwards, the left eye is on the +X side.
+        let left = rays(&camera, 0, 48).remove(0);
+        assert!(left.origin.x > 0.0);
+    }
+}
@@ -0,0 +1,277 @@
+// cameras/thin_lens_camera.rs - Cameras with depth of field.
+// Written by quadfault
+// 10/18/26
+
+use std::f64::consts::PI;
+
+use crate::math::{ Point, Ray, Vector };
+use crate::samplers::Sampler;
+
+use super::{ Camera, LookAt, image_point };
+
+// A perspective camera with a lens instead of a pinhole. Rays start at
+// random points on the lens and pass through the point they would have hit
+// on the plane of focus, so that only things near that plane are sharp.
+pub struct ThinLensCamera {
+    image_width: usize,
+    image_height: usize,
+    samples_per_pixel: usize,
+    origin: Point,
+    lower_left_corner: Point,
+    horizontal: Vector,
+    vertical: Vector,
+    u: Vector,
+    v: Vector,
+    lens_radius: f64,
+    aperture: Aperture,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThinLens {
+    // In scene units, like the focus distance.
+    pub focal_length: f64,
+    // The f-number: the focal length over the aperture's diameter.
+    pub f_stop: f64,
+    // From the eye to the plane of focus.
+    pub focus_distance: f64,
+    pub aperture: Aperture,
+}
+
+// The shape of the aperture, which is also the shape out-of-focus points
+// of light take on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aperture {
+    Circle,
+    // A regular polygon with a corner `rotation` degrees anticlockwise from
+    // the right, as made by a diaphragm with that many blades.
+    Polygon { blades: u32, rotation: f64 },
+}
+
+impl ThinLens {
+    pub fn aperture_diameter(&self) -> f64 {
+        self.focal_length / self.f_stop
+    }
+}
+
+impl ThinLensCamera {
+    // `vertical_fov` is in degrees.
+    pub fn new(image_width: usize,
+               image_height: usize,
+               view: LookAt,
+               vertical_fov: f64,
+               aspect_ratio: f64,
+               lens: ThinLens,
+               samples_per_pixel: usize)
+        -> Self
+    {
+        let half_height = (vertical_fov.to_radians() / 2.0).tan();
+        let half_width = aspect_ratio * half_height;
+        let (u, v, w) = view.basis();
+        let d = lens.focus_distance;
+
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            origin: view.eye,
+            lower_left_corner: view.eye
+                - u * (half_width * d)
+                - v * (half_height * d)
+                - w * d,
+            horizontal: u * (2.0 * half_width * d),
+            vertical: v * (2.0 * half_height * d),
+            u,
+            v,
+            lens_radius: lens.aperture_diameter() / 2.0,
+            aperture: lens.aperture,
+        }
+    }
+
+    // The ray through a point in the image, with (0, 0) at the bottom left
+    // and (1, 1) at the top right, from a point on the lens given in units
+    // of the lens radius.
+    fn ray(&self, s: f64, t: f64, lens: (f64, f64)) -> Ray {
+        let offset = self.u * (lens.0 * self.lens_radius)
+                   + self.v * (lens.1 * self.lens_radius);
+        let origin = self.origin + offset;
+
+        Ray::new(
+            origin,
+            self.lower_left_corner
+                + self.horizontal * s
+                + self.vertical * t
+                - origin,
+        )
+    }
+}
+
+impl Camera for ThinLensCamera {
+    fn get_image_width(&self) -> usize {
+        self.image_width
+    }
+
+    fn get_image_height(&self) -> usize {
+        self.image_height
+    }
+
+    fn get_samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    fn get_ray(&self, x: f64, y: f64, sampler: &mut dyn Sampler)
+        -> Option<Ray>
+    {
+        let (s, t) = image_point(x, y, self.image_width, self.image_height);
+        let (a, b) = sampler.get_2d();
+
+        Some(self.ray(s, t, self.aperture.sample(a, b)))
+    }
+}
+
+impl Aperture {
+    // Maps a point in the unit square to a point in the aperture, scaled to
+    // fit the unit circle, spreading points evenly over its area.
+    pub fn sample(&self, a: f64, b: f64) -> (f64, f64) {
+        match *self {
+            Aperture::Circle => concentric_disk(a, b),
+            Aperture::Polygon { blades, rotation } => {
+                // Pick one of the triangles the polygon fans into from its
+                // centre, then a point within it.
+                let blades = blades.max(3);
+                let scaled = a * blades as f64;
+                let i = (scaled as u32).min(blades - 1);
+                let a = scaled - i as f64;
+
+                let step = 2.0 * PI / blades as f64;
+                let angle = rotation.to_radians() + step * i as f64;
+                let corner = |angle: f64| (angle.cos(), angle.sin());
+                let (p, q) = (corner(angle), corner(angle + step));
+
+                // Folding the unit square onto a triangle with the square
+                // root keeps the density even.
+                let r = a.sqrt();
+                let (wp, wq) = (r * (1.0 - b), r * b);
+                (wp * p.0 + wq * q.0, wp * p.1 + wq * q.1)
+            }
+        }
+    }
+}
+
+// Shirley and Chiu's mapping from the unit square to the unit disk, which
+// keeps neighbouring points together.
+fn concentric_disk(a: f64, b: f64) -> (f64, f64) {
+    let (a, b) = (2.0 * a - 1.0, 2.0 * b - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::super::tests::rays;
+    use super::*;
+
+    fn camera(aperture: Aperture) -> ThinLensCamera {
+        ThinLensCamera::new(
+            100,
+            100,
+            LookAt::new(
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(0.0, 0.0, -1.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            60.0,
+            1.0,
+            ThinLens {
+                focal_length: 0.05,
+                f_stop: 0.5,
+                focus_distance: 4.0,
+                aperture,
+            },
+            16,
+        )
+    }
+
+    fn lens_samples(aperture: Aperture) -> Vec<(f64, f64)> {
+        let mut rng = StdRng::from_seed([5; 32]);
+
+        (0..1000).map(|_| aperture.sample(rng.gen(), rng.gen())).collect()
+    }
+
+    // Where a ray crosses the plane z = `z`.
+    fn crossing(ray: &Ray, z: f64) -> Point {
+        ray.at((z - ray.origin.z) / ray.direction.z)
+    }
+
+    #[test]
+    fn in_focus_points_stay_sharp() {
+        let camera = camera(Aperture::Circle);
+        let lens = lens_samples(Aperture::Circle);
+
+        for &(s, t) in &[(0.5, 0.5), (0.1, 0.8), (0.95, 0.02)] {
+            let centre = crossing(&camera.ray(s, t, (0.0, 0.0)), -4.0);
+            let mut blur: f64 = 0.0;
+
+            for &l in &lens {
+                let ray = camera.ray(s, t, l);
+                assert!((crossing(&ray, -4.0) - centre).norm() < 1e-12);
+
+                let near = crossing(&camera.ray(s, t, (0.0, 0.0)), -1.0);
+                blur = blur.max((crossing(&ray, -1.0) - near).norm());
+            }
+
+            // Off the plane of focus, rays from across the lens spread out.
+            assert!(blur > 0.02);
+        }
+    }
+
+    #[test]
+    fn aperture_size_follows_f_stop() {
+        let camera = camera(Aperture::Circle);
+
+        for ray in rays(&camera, 50, 50) {
+            let offset = ray.origin - Point::new(0.0, 0.0, 0.0);
+            assert!(offset.norm() <= 0.05 + 1e-12);
+            assert!(offset.z.abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn circular_aperture_fills_the_disk() {
+        let samples = lens_samples(Aperture::Circle);
+
+        assert!(samples.iter().all(|&(x, y)| x * x + y * y <= 1.0 + 1e-12));
+        // About a quarter of an even spread lands within half the radius.
+        let inner = samples.iter()
+            .filter(|&&(x, y)| x * x + y * y < 0.25)
+            .count();
+        assert!(inner > 200 && inner < 300);
+    }
+
+    #[test]
+    fn polygonal_aperture_stays_inside() {
+        let aperture = Aperture::Polygon { blades: 6, rotation: 30.0 };
+        let samples = lens_samples(aperture);
+
+        // A hexagon with corners at 30 and 90 degrees and so on has its
+        // edges 60 degrees apart, starting at 0 degrees.
+        let apothem = (PI / 6.0).cos();
+        for &(x, y) in &samples {
+            for i in 0..6 {
+                let angle = PI / 3.0 * i as f64;
+                assert!(x * angle.cos() + y * angle.sin() <= apothem + 1e-12);
+            }
+        }
+        assert!(samples.iter().any(|&(x, _)| x > 0.8));
+    }
+}
@@ -0,0 +1,381 @@
+// cli.rs - Command-line arguments.
+// Written by quadfault
+// 10/18/26
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use rt::image::ImageFormat;
+use rt::loaders::{ RenderSettings, SamplerDesc, SceneFile };
+
+pub const USAGE: &str = "\
+usage: rt [OPTIONS] SCENE_FILE
+
+Renders SCENE_FILE. Options override the scene file's render settings.
+
+options:
+  -o, --output PATH    write the image to PATH instead of standard output;
+                       the format comes from the extension: .ppm or .png,
+                       or .pfm, .hdr or .exr for linear, unclamped colour
+      --width N        image width in pixels
+      --height N       image height in pixels
+      --spp N          samples per pixel
+      --max-depth N    bounces after which a path is cut off
+      --threads N      number of render threads (default: one per core)
+      --seed N         seed for the random number generators
+      --sampler NAME   how to pick samples: independent, stratified, halton
+                       or sobol
+      --max-spp N      sample adaptively, taking --spp samples per pixel
+                       and more, up to N, where the image is noisy
+      --error E        the relative error at which adaptive sampling stops
+                       taking more samples in a pixel (default: 0.01)
+      --heatmap PATH   also write an image of how many samples each pixel
+                       took, brightest where it took the most
+      --progressive N  render in passes of N samples per pixel, writing the
+                       image so far after each pass, until every pixel has
+                       --spp samples
+      --time-limit S   render progressively, stopping after the first pass
+                       to end more than S seconds in
+      --snapshot-every S
+                       write the image so far at most every S seconds,
+                       between passes or, if not rendering progressively,
+                       between tiles
+  -h, --help           print this message and exit";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    // Boxed, since it's so much bigger than the other commands.
+    Render(Box<Options>),
+    Help,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub scene: PathBuf,
+    pub output: Option<PathBuf>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub samples_per_pixel: Option<usize>,
+    pub max_depth: Option<i32>,
+    pub threads: Option<usize>,
+    pub seed: Option<u64>,
+    pub sampler: Option<SamplerDesc>,
+    pub max_samples: Option<usize>,
+    pub error: Option<f32>,
+    pub heatmap: Option<PathBuf>,
+    pub pass_samples: Option<usize>,
+    pub time_limit: Option<f64>,
+    pub snapshot_every: Option<f64>,
+}
+
+impl Options {
+    // Applies the options to the scene file's settings, then checks that
+    // its camera can make an image of the size they end up with.
+    pub fn configure(&self, file: &mut SceneFile) -> Result<(), String> {
+        self.apply(&mut file.settings);
+
+        file.camera.check_image_size(&file.settings)
+    }
+
+    pub fn apply(&self, settings: &mut RenderSettings) {
+        if let Some(width) = self.width {
+            settings.width = width;
+        }
+        if let Some(height) = self.height {
+            settings.height = height;
+        }
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            settings.samples_per_pixel = samples_per_pixel;
+        }
+        if let Some(max_depth) = self.max_depth {
+            settings.max_depth = max_depth;
+        }
+        if self.threads.is_some() {
+            settings.threads = self.threads;
+        }
+        if self.seed.is_some() {
+            settings.seed = self.seed;
+        }
+        if let Some(sampler) = self.sampler {
+            settings.sampler = sampler;
+        }
+        if self.max_samples.is_some() {
+            settings.max_samples = self.max_samples;
+        }
+        if let Some(error) = self.error {
+            settings.error = error;
+        }
+        if self.pass_samples.is_some() {
+            settings.pass_samples = self.pass_samples;
+        }
+        if self.time_limit.is_some() {
+            settings.time_limit = self.time_limit;
+        }
+    }
+}
+
+// Parses the arguments after the program name. Options take their values
+// either as the next argument or after an '=', as in --spp=16.
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+    where I: IntoIterator<Item=String>
+{
+    let mut args = args.into_iter();
+    let mut options = Options::default();
+    let mut scene = None;
+
+    while let Some(arg) = args.next() {
+        if arg == "-" {
+            return Err("can't read the scene from standard input".into());
+        }
+        if !arg.starts_with('-') {
+            if scene.is_some() {
+                return Err(format!("unexpected argument '{}'", arg));
+            }
+            scene = Some(PathBuf::from(arg));
+            continue;
+        }
+
+        let (name, inline_value) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => {
+                (&arg[..i], Some(arg[i + 1..].to_string()))
+            }
+            _ => (&arg[..], None),
+        };
+        let mut value = || {
+            inline_value.clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-o" | "--output" => {
+                let output = PathBuf::from(value()?);
+                ImageFormat::from_path(&output)?;
+                options.output = Some(output);
+            }
+            "--width" => options.width = Some(positive(name, &value()?)?),
+            "--height" => options.height = Some(positive(name, &value()?)?),
+            "--spp" => {
+                options.samples_per_pixel = Some(positive(name, &value()?)?);
+            }
+            "--max-depth" => {
+                options.max_depth = Some(non_negative(name, &value()?)?);
+            }
+            "--threads" => {
+                options.threads = Some(positive(name, &value()?)?);
+            }
+            "--seed" => options.seed = Some(number(name, &value()?)?),
+            "--sampler" => options.sampler = Some(value()?.parse()?),
+            "--max-spp" => {
+                options.max_samples = Some(positive(name, &value()?)?);
+            }
+            "--error" => {
+                options.error = Some(fraction(name, &value()?)? as f32);
+            }
+            "--heatmap" => {
+                let heatmap = PathBuf::from(value()?);
+                ImageFormat::from_path(&heatmap)?;
+                options.heatmap = Some(heatmap);
+            }
+            "--progressive" => {
+                options.pass_samples = Some(positive(name, &value()?)?);
+            }
+            "--time-limit" => {
+                options.time_limit = Some(fraction(name, &value()?)?);
+            }
+            "--snapshot-every" => {
+                options.snapshot_every = Some(fraction(name, &value()?)?);
+            }
+            _ => return Err(format!("unknown option '{}'", name)),
+        }
+    }
+
+    options.scene = scene.ok_or("no scene file given")?;
+
+    Ok(Command::Render(Box::new(options)))
+}
+
+fn number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| {
+        format!("invalid value '{}' for {}", value, name)
+    })
+}
+
+fn positive(name: &str, value: &str) -> Result<usize, String> {
+    match number(name, value)? {
+        0 => Err(format!("{} must be at least 1", name)),
+        n => Ok(n),
+    }
+}
+
+fn non_negative(name: &str, value: &str) -> Result<i32, String> {
+    match number(name, value)? {
+        n if n < 0 => Err(format!("{} must not be negative", name)),
+        n => Ok(n),
+    }
+}
+
+// A number above zero, not necessarily whole.
+fn fraction(name: &str, value: &str) -> Result<f64, String> {
+    let x: f64 = number(name, value)?;
+
+    if x > 0.0 && x.is_finite() {
+        Ok(x)
+    } else {
+        Err(format!("{} must be positive", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rt::loaders::parse_scene;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn options() {
+        let command = parse(&[
+            "--width", "640", "--height=480", "--spp", "16",
+            "-o", "out.ppm", "scene.txt", "--seed", "7", "--threads=3",
+            "--sampler", "stratified", "--max-spp=256", "--error", "0.05",
+            "--heatmap", "spp.png", "--progressive", "4", "--time-limit=2.5",
+            "--snapshot-every", "10",
+        ]).unwrap();
+
+        assert_eq!(command, Command::Render(Box::new(Options {
+            scene: PathBuf::from("scene.txt"),
+            output: Some(PathBuf::from("out.ppm")),
+            width: Some(640),
+            height: Some(480),
+            samples_per_pixel: Some(16),
+            max_depth: None,
+            threads: Some(3),
+            seed: Some(7),
+            sampler: Some(SamplerDesc::Stratified),
+            max_samples: Some(256),
+            error: Some(0.05),
+            heatmap: Some(PathBuf::from("spp.png")),
+            pass_samples: Some(4),
+            time_limit: Some(2.5),
+            snapshot_every: Some(10.0),
+        })));
+        assert_eq!(parse(&["x", "--help"]).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn overrides() {
+        let mut settings = RenderSettings::default();
+        let options = Options {
+            width: Some(10),
+            max_depth: Some(3),
+            max_samples: Some(100),
+            ..Options::default()
+        };
+        options.apply(&mut settings);
+
+        assert_eq!(settings.width, 10);
+        assert_eq!(settings.max_depth, 3);
+        assert_eq!(settings.max_samples, Some(100));
+        assert_eq!(settings.error, RenderSettings::default().error);
+        assert_eq!(settings.height, RenderSettings::default().height);
+        assert_eq!(settings.progressive(), None);
+
+        let mut settings = RenderSettings::default();
+        let options = Options {
+            snapshot_every: Some(5.0),
+            ..Options::default()
+        };
+        options.apply(&mut settings);
+        assert_eq!(settings.progressive(), None);
+    }
+
+    #[test]
+    fn overrides_can_fix_the_image_size() {
+        let text = "camera type=cubemap\n";
+        let mut file = parse_scene(Path::new("c.scene"), text).unwrap();
+        assert_eq!(
+            Options::default().configure(&mut file).unwrap_err(),
+            "a cubemap camera needs an image 6 times as wide as it is tall, \
+             not 400x200",
+        );
+
+        let options = Options {
+            width: Some(60),
+            height: Some(10),
+            ..Options::default()
+        };
+        assert_eq!(options.configure(&mut file), Ok(()));
+        assert_eq!(file.camera.build(&file.settings).get_image_width(), 60);
+
+        // The stereo camera's eyes split the image.
+        let text = "render width=5\n\
+                    camera type=stereo projection=equirectangular ipd=0.1\n";
+        let mut file = parse_scene(Path::new("s.scene"), text).unwrap();
+        let options = |width| Options {
+            width: Some(width),
+            ..Options::default()
+        };
+        assert_eq!(
+            options(1).configure(&mut file).unwrap_err(),
+            "a stereo camera needs an even image width of at least 2 to split \
+             between the eyes, not 1",
+        );
+        assert_eq!(options(8).configure(&mut file), Ok(()));
+        assert_eq!(file.camera.build(&file.settings).get_image_width(), 8);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(&[]).unwrap_err(), "no scene file given");
+        assert_eq!(parse(&["a", "b"]).unwrap_err(), "unexpected argument 'b'");
+        assert_eq!(
+            parse(&["a", "--spp"]).unwrap_err(),
+            "--spp needs a value",
+        );
+        assert_eq!(
+            parse(&["a", "--width", "wide"]).unwrap_err(),
+            "invalid value 'wide' for --width",
+        );
+        assert_eq!(
+            parse(&["a", "--threads=0"]).unwrap_err(),
+            "--threads must be at least 1",
+        );
+        assert_eq!(
+            parse(&["a", "--max-depth", "-1"]).unwrap_err(),
+            "--max-depth must not be negative",
+        );
+        assert_eq!(
+            parse(&["-"]).unwrap_err(),
+            "can't read the scene from standard input",
+        );
+        assert_eq!(
+            parse(&["a", "--error=-1"]).unwrap_err(),
+            "--error must be positive",
+        );
+        assert_eq!(
+            parse(&["a", "--time-limit", "0"]).unwrap_err(),
+            "--time-limit must be positive",
+        );
+        assert_eq!(
+            parse(&["a", "--sampler", "sobel"]).unwrap_err(),
+            "unknown sampler 'sobel' (expected independent, stratified, \
+             halton or sobol)",
+        );
+        assert_eq!(
+            parse(&["a", "--frobnicate"]).unwrap_err(),
+            "unknown option '--frobnicate'",
+        );
+        assert_eq!(
+            parse(&["a", "-o", "out.gif"]).unwrap_err(),
+            "unsupported output format '.gif' (expected .ppm, .png, .pfm, \
+             .hdr or .exr)",
+        );
+    }
+}
@@ -0,0 +1,302 @@
+// film.rs - Film, which collects samples into pixels.
+// Written by quadfault
+// 10/18/26
+
+use crate::filters::{ BoxFilter, Filter };
+use crate::image::Image;
+use crate::math::Color;
+use crate::tile::Tile;
+
+// Pixels are addressed like the camera addresses them: x from the left and
+// y from the bottom. Samples are placed anywhere on the film, in pixels from
+// its bottom left corner, and the filter spreads each over the pixels whose
+// centres are near it.
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<FilmPixel>,
+    filter: Box<dyn Filter>,
+}
+
+// The samples for one tile of a film, so that threads can each fill in
+// their own and merge them into the film when they're done. Samples in the
+// tile reach pixels around it too, so the tile keeps those as well.
+pub(crate) struct FilmTile {
+    tile: Tile,
+    area: Tile,
+    pixels: Vec<FilmPixel>,
+    filter: Box<dyn Filter>,
+}
+
+// A pixel counts the samples taken inside it separately from those it's
+// weighed, which with a wide filter come from its neighbours too.
+#[derive(Clone, Copy, Debug)]
+struct FilmPixel {
+    weighted_sum: Color,
+    weight_sum: f32,
+    samples: u32,
+}
+
+impl FilmPixel {
+    fn new() -> Self {
+        Self {
+            weighted_sum: Color::black(),
+            weight_sum: 0.0,
+            samples: 0,
+        }
+    }
+
+    fn add(&mut self, color: Color, weight: f32) {
+        self.weighted_sum += color * weight;
+        self.weight_sum += weight;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.weighted_sum += other.weighted_sum;
+        self.weight_sum += other.weight_sum;
+        self.samples += other.samples;
+    }
+
+    fn color(&self) -> Color {
+        if self.weight_sum > 0.0 {
+            self.weighted_sum * (1.0 / self.weight_sum)
+        } else {
+            Color::black()
+        }
+    }
+}
+
+impl Film {
+    // A film that averages the samples inside each pixel.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_filter(width, height, Box::new(BoxFilter::new(0.5)))
+    }
+
+    pub fn with_filter(width: usize, height: usize, filter: Box<dyn Filter>)
+        -> Self
+    {
+        Self {
+            width,
+            height,
+            pixels: vec![FilmPixel::new(); width * height],
+            filter,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Color) {
+        let area = Tile { x0: 0, y0: 0, x1: self.width, y1: self.height };
+        splat(&*self.filter, area, &mut self.pixels, x, y, color);
+    }
+
+    pub(crate) fn tile(&self, tile: Tile) -> FilmTile {
+        let pad = (self.filter.radius() + 0.5).ceil() as usize;
+        let area = Tile {
+            x0: tile.x0.saturating_sub(pad),
+            y0: tile.y0.saturating_sub(pad),
+            x1: (tile.x1 + pad).min(self.width),
+            y1: (tile.y1 + pad).min(self.height),
+        };
+
+        FilmTile {
+            tile,
+            area,
+            pixels: vec![FilmPixel::new(); area.width() * area.height()],
+            filter: self.filter.clone_box(),
+        }
+    }
+
+    pub(crate) fn merge_tile(&mut self, film_tile: &FilmTile) {
+        let area = film_tile.area;
+        let rows = film_tile.pixels.chunks(area.width());
+
+        for (y, row) in (area.y0..area.y1).zip(rows) {
+            let start = y * self.width + area.x0;
+            for (pixel, other) in self.pixels[start..].iter_mut().zip(row) {
+                pixel.merge(other);
+            }
+        }
+    }
+
+    // The weighted average of each pixel's samples, as a linear image.
+    pub fn image(&self) -> Image {
+        self.to_image(FilmPixel::color)
+    }
+
+    // The number of samples taken in each pixel, row by row from the bottom
+    // left like the pixels are addressed.
+    pub fn sample_counts(&self) -> Vec<u32> {
+        self.pixels.iter().map(|pixel| pixel.samples).collect()
+    }
+
+    // The number of samples taken in each pixel, relative to the most any
+    // pixel took, shading from black through red and yellow to white.
+    pub fn heatmap(&self) -> Image {
+        let most = self.pixels.iter().map(|pixel| pixel.samples).max();
+        let most = most.unwrap_or(0).max(1) as f32;
+
+        self.to_image(|pixel| heat(pixel.samples as f32 / most))
+    }
+
+    fn to_image<F>(&self, f: F) -> Image
+        where F: Fn(&FilmPixel) -> Color
+    {
+        let pixels = self.pixels.chunks(self.width)
+            .rev()
+            .flat_map(|row| row.iter().map(&f))
+            .collect();
+
+        Image::new(self.width, self.height, pixels)
+    }
+}
+
+impl FilmTile {
+    // The pixels this tile takes samples for.
+    pub fn bounds(&self) -> Tile {
+        self.tile
+    }
+
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Color) {
+        let tile = self.tile;
+        debug_assert!(tile.x0 as f64 <= x && x <= tile.x1 as f64);
+        debug_assert!(tile.y0 as f64 <= y && y <= tile.y1 as f64);
+
+        splat(&*self.filter, self.area, &mut self.pixels, x, y, color);
+    }
+}
+
+// Adds a sample at (x, y) to each pixel in `area` whose centre is within the
+// filter's radius of it, where `pixels` holds the area row by row. The range
+// is half-open, so that with a box filter of radius 0.5 a sample on the
+// edge between two pixels counts towards just one of them.
+fn splat(filter: &dyn Filter,
+         area: Tile,
+         pixels: &mut [FilmPixel],
+         x: f64,
+         y: f64,
+         color: Color)
+{
+    let inside = |p: f64, lo: usize, hi: usize| {
+        (p.max(0.0) as usize).clamp(lo, hi - 1) - lo
+    };
+    let i = inside(y, area.y0, area.y1) * area.width()
+        + inside(x, area.x0, area.x1);
+    pixels[i].samples += 1;
+
+    let r = filter.radius();
+    let range = |p: f64, lo: usize, hi: usize| {
+        let first = (p - 0.5 - r).floor() + 1.0;
+        let last = (p - 0.5 + r).floor();
+
+        (first.max(lo as f64) as usize)..((last + 1.0).min(hi as f64) as usize)
+    };
+
+    for py in range(y, area.y0, area.y1) {
+        let wy = filter.evaluate(py as f64 + 0.5 - y);
+        let row = (py - area.y0) * area.width();
+
+        for px in range(x, area.x0, area.x1) {
+            let weight = wy * filter.evaluate(px as f64 + 0.5 - x);
+            if weight != 0.0 {
+                pixels[row + px - area.x0].add(color, weight as f32);
+            }
+        }
+    }
+}
+
+// Shades 0 to 1 from black through red and yellow to white.
+fn heat(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0) * 3.0;
+
+    Color::new(t.min(1.0), (t - 1.0).clamp(0.0, 1.0), (t - 2.0).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filters::TentFilter;
+
+    use super::*;
+
+    #[test]
+    fn box_filter_averages_each_pixel() {
+        let mut film = Film::new(2, 1);
+        film.add_sample(0.2, 0.5, Color::new(1.0, 0.0, 0.0));
+        film.add_sample(0.7, 0.5, Color::new(0.0, 1.0, 0.0));
+        film.add_sample(1.0, 0.5, Color::new(0.0, 0.0, 1.0));
+
+        let image = film.image();
+        assert_eq!(image.get(0, 0), Color::new(0.5, 0.5, 0.0));
+        assert_eq!(image.get(1, 0), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn wide_filters_reach_neighbouring_pixels() {
+        let filter = Box::new(TentFilter::new(1.0));
+        let mut film = Film::with_filter(3, 1, filter);
+        film.add_sample(1.0, 0.5, Color::new(1.0, 0.0, 0.0));
+        film.add_sample(1.5, 0.5, Color::new(0.0, 1.0, 0.0));
+
+        // Pixel 1's centre is half a pixel from the first sample and right
+        // on the second, so it weighs them 0.5 and 1.
+        let image = film.image();
+        assert_eq!(image.get(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(image.get(1, 0), Color::new(1.0 / 3.0, 2.0 / 3.0, 0.0));
+        assert_eq!(image.get(2, 0), Color::black());
+    }
+
+    #[test]
+    fn tiles_merge_into_place() {
+        let mut film = Film::new(4, 3);
+        let mut tile = film.tile(Tile { x0: 2, y0: 1, x1: 4, y1: 3 });
+        tile.add_sample(3.5, 2.5, Color::new(1.0, 1.0, 1.0));
+        film.add_sample(3.5, 2.5, Color::new(0.0, 0.0, 0.0));
+        film.merge_tile(&tile);
+
+        // The image comes out top row first.
+        let image = film.image();
+        assert_eq!(image.get(3, 0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(image.get(3, 1), Color::black());
+    }
+
+    #[test]
+    fn samples_are_counted_where_they_land() {
+        let filter = Box::new(TentFilter::new(1.5));
+        let mut film = Film::with_filter(3, 2, filter);
+        let mut tile = film.tile(Tile { x0: 2, y0: 0, x1: 3, y1: 2 });
+        film.add_sample(0.5, 0.5, Color::black());
+        film.add_sample(0.9, 0.1, Color::black());
+        tile.add_sample(2.5, 1.5, Color::black());
+        film.merge_tile(&tile);
+
+        assert_eq!(film.sample_counts(), [2, 0, 0, 0, 0, 1]);
+
+        let heatmap = film.heatmap();
+        assert_eq!(heatmap.get(0, 1), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(heatmap.get(2, 0), Color::new(1.0, 0.5, 0.0));
+        assert_eq!(heatmap.get(1, 0), Color::black());
+    }
+
+    #[test]
+    fn tiles_keep_samples_that_spill_over() {
+        let filter = || Box::new(TentFilter::new(2.0));
+        let mut direct = Film::with_filter(6, 6, filter());
+        let mut tiled = Film::with_filter(6, 6, filter());
+        let mut tile = tiled.tile(Tile { x0: 2, y0: 2, x1: 4, y1: 4 });
+
+        for &(x, y) in &[(2.0, 2.0), (3.3, 2.9), (4.0, 4.0)] {
+            let color = Color::new(x as f32, y as f32, 1.0);
+            direct.add_sample(x, y, color);
+            tile.add_sample(x, y, color);
+        }
+        tiled.merge_tile(&tile);
+
+        assert_eq!(tiled.image().pixels(), direct.image().pixels());
+    }
+}
@@ -0,0 +1,32 @@
+// filters/box_filter.rs - Box filters.
+// Written by quadfault
+// 10/18/26
+
+use super::Filter;
+
+// Weighs every sample within the radius equally. With a radius of half a
+// pixel, each pixel is the plain average of the samples inside it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoxFilter {
+    radius: f64,
+}
+
+impl BoxFilter {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        if x.abs() <= self.radius { 1.0 } else { 0.0 }
+    }
+
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,52 @@
+// filters/gaussian_filter.rs - Gaussian filters.
+// Written by quadfault
+// 10/18/26
+
+use super::Filter;
+
+// A Gaussian with standard deviation `sigma`, lowered so that it reaches
+// zero at the radius instead of stopping short with a step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GaussianFilter {
+    radius: f64,
+    sigma: f64,
+    edge: f64,
+}
+
+impl GaussianFilter {
+    pub fn new(radius: f64, sigma: f64) -> Self {
+        Self { radius, sigma, edge: gaussian(radius, sigma) }
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        (gaussian(x, self.sigma) - self.edge).max(0.0)
+    }
+
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(*self)
+    }
+}
+
+fn gaussian(x: f64, sigma: f64) -> f64 {
+    (-x * x / (2.0 * sigma * sigma)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaches_zero_at_the_radius() {
+        let filter = GaussianFilter::new(1.5, 0.5);
+
+        assert_eq!(filter.evaluate(1.5), 0.0);
+        assert!(filter.evaluate(1.4) > 0.0);
+        assert!(filter.evaluate(0.5) < filter.evaluate(0.25));
+    }
+}
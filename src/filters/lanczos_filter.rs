@@ -0,0 +1,62 @@
+// filters/lanczos_filter.rs - Lanczos windowed sinc filters.
+// Written by quadfault
+// 10/18/26
+
+use std::f64::consts::PI;
+
+use super::Filter;
+
+// The sinc function, the ideal low-pass filter, windowed by a wider sinc
+// so that it comes to zero at the radius. Sharp, but rings the most.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LanczosFilter {
+    radius: f64,
+}
+
+impl LanczosFilter {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        if x.abs() < self.radius {
+            sinc(x) * sinc(x / self.radius)
+        } else {
+            0.0
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(*self)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_at_whole_pixels() {
+        let filter = LanczosFilter::new(3.0);
+
+        assert_eq!(filter.evaluate(0.0), 1.0);
+        for &x in &[1.0, 2.0, -1.0] {
+            assert!(filter.evaluate(x).abs() < 1e-12);
+        }
+        assert!(filter.evaluate(1.5) < 0.0);
+    }
+}
@@ -0,0 +1,71 @@
+// filters/mitchell_filter.rs - Mitchell-Netravali filters.
+// Written by quadfault
+// 10/18/26
+
+use super::Filter;
+
+// The family of cubic filters from Mitchell and Netravali's "Reconstruction
+// Filters in Computer Graphics", stretched over the radius. B and C trade
+// blurring against ringing; they recommend B = C = 1/3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MitchellFilter {
+    radius: f64,
+    b: f64,
+    c: f64,
+}
+
+impl MitchellFilter {
+    pub fn new(radius: f64, b: f64, c: f64) -> Self {
+        Self { radius, b, c }
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    // The cubic is defined over [-2, 2].
+    fn evaluate(&self, x: f64) -> f64 {
+        let x = (2.0 * x / self.radius).abs();
+        let (b, c) = (self.b, self.c);
+
+        let weight = if x < 1.0 {
+            (12.0 - 9.0 * b - 6.0 * c) * x * x * x
+                + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+                + (6.0 - 2.0 * b)
+        } else if x < 2.0 {
+            (-b - 6.0 * c) * x * x * x
+                + (6.0 * b + 30.0 * c) * x * x
+                + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c)
+        } else {
+            0.0
+        };
+
+        weight / 6.0
+    }
+
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::integral;
+    use super::*;
+
+    #[test]
+    fn shape() {
+        let filter = MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0);
+
+        // With B + 2C = 1, the weights of a grid of samples add up to one;
+        // over the continuous filter, that's an integral of one pixel.
+        assert!((integral(&filter) - 1.0).abs() < 1e-6);
+        assert!((filter.evaluate(0.0) - 16.0 / 18.0).abs() < 1e-12);
+        // It dips below zero between one and two pixels out.
+        assert!(filter.evaluate(1.5) < 0.0);
+        assert!(filter.evaluate(2.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,71 @@
+// filters/mod.rs - Reconstruction filters, which decide how much each
+// sample counts towards the pixels around it.
+// Written by quadfault
+// 10/18/26
+
+mod box_filter;
+mod gaussian_filter;
+mod lanczos_filter;
+mod mitchell_filter;
+mod tent_filter;
+
+pub use self::box_filter::*;
+pub use self::gaussian_filter::*;
+pub use self::lanczos_filter::*;
+pub use self::mitchell_filter::*;
+pub use self::tent_filter::*;
+
+// Every filter here is separable: the weight of a sample at (x, y) from a
+// pixel's centre is evaluate(x) * evaluate(y). Distances are in pixels.
+pub trait Filter: Send + Sync {
+    // How far from a pixel's centre samples still count towards it.
+    fn radius(&self) -> f64;
+
+    // The weight at distance x, which is zero beyond the radius. Filters
+    // with negative lobes sharpen the image but can ring around edges.
+    fn evaluate(&self, x: f64) -> f64;
+
+    // A copy of the filter, for another film.
+    fn clone_box(&self) -> Box<dyn Filter>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The area under the filter, which every filter should have positive
+    // for weights to make sense.
+    pub fn integral(filter: &dyn Filter) -> f64 {
+        let r = filter.radius();
+        let n = 10_000;
+
+        (0..n)
+            .map(|i| {
+                let x = -r + 2.0 * r * (i as f64 + 0.5) / n as f64;
+                filter.evaluate(x) * 2.0 * r / n as f64
+            })
+            .sum()
+    }
+
+    #[test]
+    fn filters_are_symmetric_and_bounded() {
+        let filters: Vec<Box<dyn Filter>> = vec![
+            Box::new(BoxFilter::new(0.5)),
+            Box::new(TentFilter::new(1.0)),
+            Box::new(GaussianFilter::new(1.5, 0.5)),
+            Box::new(MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0)),
+            Box::new(LanczosFilter::new(3.0)),
+        ];
+
+        for filter in &filters {
+            let r = filter.radius();
+            for i in 0..=100 {
+                let x = r * i as f64 / 100.0;
+                assert_eq!(filter.evaluate(x), filter.evaluate(-x));
+            }
+            assert_eq!(filter.evaluate(r * 1.01), 0.0);
+            assert!(filter.evaluate(0.0) > 0.0);
+            assert!(integral(&**filter) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// filters/tent_filter.rs - Tent (triangle) filters.
+// Written by quadfault
+// 10/18/26
+
+use super::Filter;
+
+// Falls off linearly from the centre to zero at the radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TentFilter {
+    radius: f64,
+}
+
+impl TentFilter {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        (self.radius - x.abs()).max(0.0)
+    }
+
+    fn clone_box(&self) -> Box<dyn Filter> {
+        Box::new(*self)
+    }
+}
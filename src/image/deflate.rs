@@ -0,0 +1,437 @@
+// image/deflate.rs - zlib streams (RFC 1950) of DEFLATE data (RFC 1951.)
+// Written by quadfault
+// 10/18/26
+
+// Compresses with LZ77 over a 32K window, coded with the fixed Huffman codes,
+// falling back on stored (uncompressed) blocks when that comes out smaller.
+// Dynamic Huffman codes would compress better, but images are written once
+// and the fixed codes keep this short.
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 64;
+const MAX_STORED_BLOCK: usize = 65535;
+
+// Base lengths and extra bits of length codes 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Base distances and extra bits of distance codes 0 to 29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, no preset dictionary. The check bits make
+    // the header a multiple of 31.
+    let mut out = vec![0x78, 0x01];
+
+    let compressed = deflate_fixed(data);
+    if compressed.len() < stored_len(data.len()) {
+        out.extend(compressed);
+    } else {
+        out.extend(deflate_stored(data));
+    }
+
+    out.extend(&adler32(data).to_be_bytes());
+    out
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    // 5552 is the most bytes that can be summed before b might overflow.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn stored_len(n: usize) -> usize {
+    let blocks = n.div_ceil(MAX_STORED_BLOCK).max(1);
+
+    n + 5 * blocks
+}
+
+fn deflate_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(stored_len(data.len()));
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+
+    if chunks.peek().is_none() {
+        // An empty final stored block.
+        out.extend(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+
+        // BFINAL and BTYPE = 00, padded out to the byte boundary.
+        out.push(last as u8);
+        out.extend(&len.to_le_bytes());
+        out.extend(&(!len).to_le_bytes());
+        out.extend(chunk);
+    }
+
+    out
+}
+
+// A single final block with the fixed Huffman codes.
+fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::new();
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut matcher = Matcher::new(data);
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = matcher.longest_match(i);
+
+        if length >= MIN_MATCH {
+            write_length(&mut bits, length);
+            write_distance(&mut bits, distance);
+            for j in i..i + length {
+                matcher.insert(j);
+            }
+            i += length;
+        } else {
+            write_literal(&mut bits, data[i]);
+            matcher.insert(i);
+            i += 1;
+        }
+    }
+
+    write_literal_length_code(&mut bits, 256);
+    bits.finish()
+}
+
+// Finds earlier occurrences of the data at a position through chains of
+// positions whose next three bytes hash the same.
+struct Matcher<'a> {
+    data: &'a [u8],
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            prev: vec![usize::MAX; WINDOW_SIZE],
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        let h = (u32::from(self.data[i]) << 16)
+            | (u32::from(self.data[i + 1]) << 8)
+            | u32::from(self.data[i + 2]);
+
+        (h.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, i: usize) {
+        if i + MIN_MATCH <= self.data.len() {
+            let h = self.hash(i);
+            self.prev[i % WINDOW_SIZE] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    // The length and distance of the longest earlier match for the data at
+    // `i`, or a length of zero if there is none.
+    fn longest_match(&self, i: usize) -> (usize, usize) {
+        if i + MIN_MATCH > self.data.len() {
+            return (0, 0);
+        }
+
+        let max_length = MAX_MATCH.min(self.data.len() - i);
+        let mut best = (0, 0);
+        let mut candidate = self.head[self.hash(i)];
+
+        for _ in 0..MAX_CHAIN {
+            if candidate == usize::MAX || i - candidate > WINDOW_SIZE {
+                break;
+            }
+
+            let length = self.data[candidate..]
+                .iter()
+                .zip(&self.data[i..i + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length > best.0 {
+                best = (length, i - candidate);
+                if length == max_length {
+                    break;
+                }
+            }
+
+            // Slots in `prev` get reused as the window slides, so a chain
+            // that doesn't go strictly backwards has run into newer entries.
+            let next = self.prev[candidate % WINDOW_SIZE];
+            if next == usize::MAX || next >= candidate {
+                break;
+            }
+            candidate = next;
+        }
+
+        best
+    }
+}
+
+fn write_literal(bits: &mut BitWriter, byte: u8) {
+    write_literal_length_code(bits, u16::from(byte));
+}
+
+fn write_length(bits: &mut BitWriter, length: usize) {
+    let code = LENGTH_BASE.iter().rposition(|&b| b as usize <= length)
+        .expect("match length below the minimum");
+
+    write_literal_length_code(bits, 257 + code as u16);
+    bits.write(
+        (length - LENGTH_BASE[code] as usize) as u32,
+        u32::from(LENGTH_EXTRA[code]),
+    );
+}
+
+fn write_distance(bits: &mut BitWriter, distance: usize) {
+    let code = DISTANCE_BASE.iter().rposition(|&b| b as usize <= distance)
+        .expect("match distance of zero");
+
+    bits.write_huffman(code as u32, 5);
+    bits.write(
+        (distance - DISTANCE_BASE[code] as usize) as u32,
+        u32::from(DISTANCE_EXTRA[code]),
+    );
+}
+
+// The fixed literal/length code of RFC 1951, section 3.2.6.
+fn write_literal_length_code(bits: &mut BitWriter, symbol: u16) {
+    let symbol = u32::from(symbol);
+
+    match symbol {
+        0..=143 => bits.write_huffman(0x30 + symbol, 8),
+        144..=255 => bits.write_huffman(0x190 + symbol - 144, 9),
+        256..=279 => bits.write_huffman(symbol - 256, 7),
+        _ => bits.write_huffman(0xc0 + symbol - 280, 8),
+    }
+}
+
+// Packs bits into bytes starting from the least significant bit.
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            out: vec![],
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= u64::from(value) << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit.
+    fn write_huffman(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+
+        self.out
+    }
+}
+
+// Just enough of a decompressor to check what the compressor writes: stored
+// blocks and blocks with the fixed Huffman codes.
+#[cfg(test)]
+pub(crate) fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn bit(&mut self) -> Result<u32, String> {
+            let byte = self.data.get(self.pos / 8)
+                .ok_or("unexpected end of data")?;
+            let bit = (byte >> (self.pos % 8)) & 1;
+            self.pos += 1;
+            Ok(u32::from(bit))
+        }
+
+        fn bits(&mut self, count: u32) -> Result<u32, String> {
+            let mut value = 0;
+            for i in 0..count {
+                value |= self.bit()? << i;
+            }
+            Ok(value)
+        }
+
+        fn huffman(&mut self, count: u32) -> Result<u32, String> {
+            let mut code = 0;
+            for _ in 0..count {
+                code = (code << 1) | self.bit()?;
+            }
+            Ok(code)
+        }
+
+        fn literal_length(&mut self) -> Result<u32, String> {
+            let code = self.huffman(7)?;
+            if code <= 0x17 {
+                return Ok(code + 256);
+            }
+            let code = (code << 1) | self.bit()?;
+            match code {
+                0x30..=0xbf => Ok(code - 0x30),
+                0xc0..=0xc7 => Ok(code - 0xc0 + 280),
+                _ => Ok(((code << 1) | self.bit()?) - 0x190 + 144),
+            }
+        }
+    }
+
+    if data.len() < 6
+        || (u32::from(data[0]) * 256 + u32::from(data[1])) % 31 != 0
+    {
+        return Err("bad zlib header".into());
+    }
+
+    let mut reader = BitReader { data: &data[..data.len() - 4], pos: 16 };
+    let mut out = vec![];
+    loop {
+        let last = reader.bit()?;
+        match reader.bits(2)? {
+            0 => {
+                reader.pos = reader.pos.div_ceil(8) * 8;
+                let len = reader.bits(16)? as usize;
+                let nlen = reader.bits(16)? as usize;
+                if len != !nlen & 0xffff {
+                    return Err("stored block length mismatch".into());
+                }
+                let start = reader.pos / 8;
+                out.extend(reader.data.get(start..start + len)
+                    .ok_or("stored block runs past the end")?);
+                reader.pos += 8 * len;
+            }
+            1 => loop {
+                let symbol = reader.literal_length()?;
+                if symbol < 256 {
+                    out.push(symbol as u8);
+                } else if symbol == 256 {
+                    break;
+                } else {
+                    let code = (symbol - 257) as usize;
+                    let length = LENGTH_BASE[code] as usize
+                        + reader.bits(u32::from(LENGTH_EXTRA[code]))? as usize;
+                    let code = reader.huffman(5)? as usize;
+                    let distance = DISTANCE_BASE[code] as usize
+                        + reader.bits(u32::from(DISTANCE_EXTRA[code]))?
+                            as usize;
+                    if distance > out.len() {
+                        return Err("distance too far back".into());
+                    }
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            },
+            other => return Err(format!("unsupported block type {}", other)),
+        }
+        if last == 1 {
+            break;
+        }
+    }
+
+    let checksum = &data[data.len() - 4..];
+    if checksum != adler32(&out).to_be_bytes() {
+        return Err("adler32 mismatch".into());
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = zlib_compress(data);
+        assert_eq!(zlib_decompress(&compressed).unwrap(), data);
+
+        compressed
+    }
+
+    #[test]
+    fn adler32_known_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn empty() {
+        round_trip(&[]);
+    }
+
+    #[test]
+    fn repetitive_data_compresses() {
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+        let compressed = round_trip(&data);
+
+        assert!(compressed.len() < data.len() / 10);
+    }
+
+    #[test]
+    fn random_data_is_stored() {
+        let mut rng = StdRng::from_seed([1; 32]);
+        let data: Vec<u8> = (0..200_000).map(|_| rng.gen()).collect();
+        let compressed = round_trip(&data);
+
+        assert_eq!(compressed.len(), 2 + stored_len(data.len()) + 4);
+    }
+
+    #[test]
+    fn mixed_data() {
+        let mut rng = StdRng::from_seed([2; 32]);
+        let data: Vec<u8> = (0..50_000)
+            .map(|i| if i % 1000 < 500 { rng.gen_range(0, 4) } else { 7 })
+            .collect();
+
+        round_trip(&data);
+    }
+}
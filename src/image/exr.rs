@@ -0,0 +1,323 @@
+// image/exr.rs - OpenEXR images.
+// Written by quadfault
+// 10/18/26
+
+use std::io::{ self, Write };
+
+use crate::math::Color;
+
+use super::{ Image, ImageWriter };
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+// Single-part scanline file, version 2.
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+impl ExrPixelType {
+    fn code(self) -> i32 {
+        match self {
+            ExrPixelType::Half => 1,
+            ExrPixelType::Float => 2,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ExrPixelType::Half => 2,
+            ExrPixelType::Float => 4,
+        }
+    }
+}
+
+// Linear, uncompressed OpenEXR images.
+pub struct ExrWriter {
+    pub pixel_type: ExrPixelType,
+}
+
+impl ImageWriter for ExrWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        write_exr(
+            out,
+            image.width(),
+            image.height(),
+            self.pixel_type,
+            image.pixels(),
+        )
+    }
+}
+
+// Writes linear colours, row by row from the top left, as an uncompressed
+// scanline OpenEXR image with R, G and B channels.
+pub fn write_exr(out: &mut dyn Write,
+                 width: usize,
+                 height: usize,
+                 pixel_type: ExrPixelType,
+                 pixels: &[Color])
+    -> io::Result<()>
+{
+    assert_eq!(pixels.len(), width * height,
+               "wrong number of pixels for a {}x{} image", width, height);
+
+    let mut header = vec![];
+    header.extend(&MAGIC);
+    header.extend(&VERSION);
+
+    // Channels must be listed in alphabetical order.
+    let mut channels = vec![];
+    for name in &["B", "G", "R"] {
+        channels.extend(name.as_bytes());
+        channels.push(0);
+        channels.extend(&pixel_type.code().to_le_bytes());
+        // pLinear and three reserved bytes, then x and y sampling.
+        channels.extend(&[0, 0, 0, 0]);
+        channels.extend(&1i32.to_le_bytes());
+        channels.extend(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let mut window = vec![];
+    for &v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend(&v.to_le_bytes());
+    }
+
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(&mut header, "pixelAspectRatio", "float",
+              &1f32.to_le_bytes());
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(&mut header, "screenWindowWidth", "float",
+              &1f32.to_le_bytes());
+    header.push(0);
+
+    // One scanline per chunk: its y, its size, then each channel in turn.
+    let line_size = 3 * width * pixel_type.size();
+    let chunk_size = 8 + line_size;
+    let table_end = header.len() + 8 * height;
+    for y in 0..height {
+        let offset = (table_end + y * chunk_size) as u64;
+        header.extend(&offset.to_le_bytes());
+    }
+    out.write_all(&header)?;
+
+    let channels: [fn(&Color) -> f32; 3] = [|c| c.b, |c| c.g, |c| c.r];
+    let mut chunk = Vec::with_capacity(chunk_size);
+    for (y, row) in pixels.chunks(width).enumerate() {
+        chunk.clear();
+        chunk.extend(&(y as i32).to_le_bytes());
+        chunk.extend(&(line_size as i32).to_le_bytes());
+
+        for channel in &channels {
+            for c in row {
+                match pixel_type {
+                    ExrPixelType::Half => {
+                        chunk.extend(&f32_to_f16(channel(c)).to_le_bytes());
+                    }
+                    ExrPixelType::Float => {
+                        chunk.extend(&channel(c).to_le_bytes());
+                    }
+                }
+            }
+        }
+        out.write_all(&chunk)?;
+    }
+
+    out.flush()
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend(&(value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+// Converts to the bits of an IEEE 754 half-precision float, rounding to the
+// nearest, ties to even.
+pub fn f32_to_f16(x: f32) -> u16 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Infinity, or NaN with at least one mantissa bit kept set.
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if half_exponent <= 0 {
+        // Subnormal, or too small even for that.
+        if half_exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        return sign | round_shift(mantissa, shift) as u16;
+    }
+
+    // Rounding can carry into the exponent, and on up to infinity, which is
+    // just what the bit pattern does when added.
+    let rounded = round_shift(mantissa, 13);
+    sign | (((half_exponent as u32) << 10) + rounded) as u16
+}
+
+pub fn f16_to_f32(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((h >> 10) & 0x1f);
+    let mantissa = f32::from(h & 0x3ff);
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+fn round_shift(value: u32, shift: u32) -> u32 {
+    let truncated = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+
+    if remainder > half || (remainder == half && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_i32(data: &[u8], pos: usize) -> i32 {
+        i32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2],
+                            data[pos + 3]])
+    }
+
+    // Reads back the channel names and values of an image write_exr wrote.
+    fn decode(exr: &[u8], width: usize, height: usize, size: usize)
+        -> Vec<[u32; 3]>
+    {
+        assert_eq!(&exr[..4], &MAGIC);
+
+        let mut pos = 8;
+        let mut attributes = vec![];
+        while exr[pos] != 0 {
+            let name_end = pos + exr[pos..].iter().position(|&b| b == 0)
+                .unwrap();
+            let name = String::from_utf8_lossy(&exr[pos..name_end])
+                .to_string();
+            let kind_end = name_end + 1
+                + exr[name_end + 1..].iter().position(|&b| b == 0).unwrap();
+            let len = read_i32(exr, kind_end + 1) as usize;
+            attributes.push(name);
+            pos = kind_end + 5 + len;
+        }
+        assert_eq!(attributes, vec![
+            "channels", "compression", "dataWindow", "displayWindow",
+            "lineOrder", "pixelAspectRatio", "screenWindowCenter",
+            "screenWindowWidth",
+        ]);
+
+        let mut pixels = vec![];
+        for y in 0..height {
+            let offset_pos = pos + 1 + 8 * y;
+            let offset = read_i32(exr, offset_pos) as usize;
+            assert_eq!(read_i32(exr, offset), y as i32);
+            assert_eq!(read_i32(exr, offset + 4) as usize, 3 * width * size);
+
+            let data = &exr[offset + 8..];
+            for x in 0..width {
+                let value = |channel: usize| {
+                    let at = (channel * width + x) * size;
+                    let mut bytes = [0; 4];
+                    bytes[..size].copy_from_slice(&data[at..at + size]);
+                    u32::from_le_bytes(bytes)
+                };
+                // Stored as B, G, R.
+                pixels.push([value(2), value(1), value(0)]);
+            }
+        }
+
+        pixels
+    }
+
+    fn pixels() -> Vec<Color> {
+        vec![
+            Color::new(1.0, 0.5, 0.25),
+            Color::new(1000.0, 0.0, -2.0),
+            Color::new(0.1, 65504.0, 1e-6),
+            Color::new(3.0, 2.0, 1.0),
+            Color::black(),
+            Color::new(0.333, 7.5, 100000.0),
+        ]
+    }
+
+    #[test]
+    fn float_channels() {
+        let mut exr = vec![];
+        write_exr(&mut exr, 3, 2, ExrPixelType::Float, &pixels()).unwrap();
+
+        let decoded = decode(&exr, 3, 2, 4);
+        for (c, d) in pixels().iter().zip(decoded) {
+            assert_eq!(d, [c.r.to_bits(), c.g.to_bits(), c.b.to_bits()]);
+        }
+    }
+
+    #[test]
+    fn half_channels() {
+        let mut exr = vec![];
+        write_exr(&mut exr, 2, 3, ExrPixelType::Half, &pixels()).unwrap();
+
+        let decoded = decode(&exr, 2, 3, 2);
+        for (c, d) in pixels().iter().zip(decoded) {
+            assert_eq!(d, [
+                u32::from(f32_to_f16(c.r)),
+                u32::from(f32_to_f16(c.g)),
+                u32::from(f32_to_f16(c.b)),
+            ]);
+        }
+    }
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(f32_to_f16(0.0), 0x0000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16(1e10), 0x7c00);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(2f32.powi(-14)), 0x0400);
+        assert_eq!(f32_to_f16(1e-10), 0x0000);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        // 1 + 2^-11 is halfway between two halves; ties go to even.
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+
+        for &x in &[0.1f32, 0.333, 7.5, 1234.5, 1e-4, -42.0] {
+            let y = f16_to_f32(f32_to_f16(x));
+            assert!(((x - y) / x).abs() < 1e-3, "{} came back as {}", x, y);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+// image/hdr.rs - Radiance RGBE images (.hdr files.)
+// Written by quadfault
+// 10/18/26
+
+use std::io::{ self, Write };
+
+use crate::math::Color;
+
+use super::{ Image, ImageWriter, invalid_data };
+
+// Linear Radiance RGBE images.
+pub struct HdrWriter;
+
+impl ImageWriter for HdrWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        write_hdr(out, image.width(), image.height(), image.pixels())
+    }
+}
+
+// Writes linear colours, row by row from the top left, as a Radiance image.
+// Scanlines are run-length encoded a channel at a time, as in Radiance's
+// own "new" RLE scheme, where the format allows it.
+pub fn write_hdr(out: &mut dyn Write,
+                 width: usize,
+                 height: usize,
+                 pixels: &[Color])
+    -> io::Result<()>
+{
+    assert_eq!(pixels.len(), width * height,
+               "wrong number of pixels for a {}x{} image", width, height);
+
+    write!(out, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n")?;
+    writeln!(out, "-Y {} +X {}", height, width)?;
+
+    for row in pixels.chunks(width) {
+        let rgbe: Vec<[u8; 4]> = row.iter().map(|&c| to_rgbe(c)).collect();
+
+        if !(8..0x8000).contains(&width) {
+            // Too narrow or too wide for RLE, so write the pixels flat.
+            for pixel in &rgbe {
+                out.write_all(pixel)?;
+            }
+            continue;
+        }
+
+        let mut line = vec![2, 2, (width >> 8) as u8, width as u8];
+        for channel in 0..4 {
+            let values: Vec<u8> = rgbe.iter().map(|p| p[channel]).collect();
+            encode_runs(&values, &mut line);
+        }
+        out.write_all(&line)?;
+    }
+
+    out.flush()
+}
+
+// Reads a Radiance image, flat or run-length encoded, with the usual
+// orientation: rows from the top down, each from left to right.
+pub fn read_hdr(input: &[u8]) -> io::Result<Image> {
+    let mut lines = HeaderLines { input, pos: 0 };
+
+    let magic = lines.next()?;
+    if magic != "#?RADIANCE" && magic != "#?RGBE" {
+        return Err(invalid_data("not a Radiance image".into()));
+    }
+    loop {
+        let line = lines.next()?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid_data(format!(
+                    "unsupported Radiance format '{}'", format
+                )));
+            }
+        }
+    }
+
+    let size = lines.next()?;
+    let (height, width) = match size.split(' ').collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (
+            height.parse::<usize>().ok(),
+            width.parse::<usize>().ok(),
+        ),
+        _ => (None, None),
+    };
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => {
+            (width, height)
+        }
+        _ => return Err(invalid_data(format!(
+            "unsupported Radiance image size '{}'", size
+        ))),
+    };
+
+    // Check that the input could hold an image that big before allocating
+    // it, so that a corrupt size can't ask for absurd amounts of memory.
+    let mut data = &input[lines.pos..];
+    let fits = min_scanline_bytes(width)
+        .and_then(|bytes| bytes.checked_mul(height))
+        .is_some_and(|bytes| bytes <= data.len());
+    if !fits {
+        return Err(invalid_data("truncated Radiance image".into()));
+    }
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut row = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        data = read_scanline(data, &mut row)?;
+        pixels.extend(row.iter().map(|&rgbe| from_rgbe(rgbe)));
+    }
+
+    Ok(Image::new(width, height, pixels))
+}
+
+// The text lines of a Radiance header, up to and including the size.
+struct HeaderLines<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> HeaderLines<'a> {
+    fn next(&mut self) -> io::Result<&'a str> {
+        let rest = &self.input[self.pos..];
+        let end = rest.iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| invalid_data("truncated Radiance header".into()))?;
+        self.pos += end + 1;
+
+        std::str::from_utf8(&rest[..end])
+            .map(|line| line.trim_end_matches('\r'))
+            .map_err(|_| invalid_data("garbled Radiance header".into()))
+    }
+}
+
+// Only rows 8 to 32767 pixels wide can be run-length encoded.
+fn can_encode(width: usize) -> bool {
+    (8..0x8000).contains(&width)
+}
+
+// The fewest bytes a row of pixels can take: four for each pixel, or if
+// it's encoded, a header and two bytes for every run of up to 127 bytes
+// in each of the four channels. None if that's too many to count.
+fn min_scanline_bytes(width: usize) -> Option<usize> {
+    if can_encode(width) {
+        Some(4 + 4 * 2 * width.div_ceil(127))
+    } else {
+        width.checked_mul(4)
+    }
+}
+
+// Reads one row of pixels, returning what follows it.
+fn read_scanline<'a>(data: &'a [u8], row: &mut [[u8; 4]])
+    -> io::Result<&'a [u8]>
+{
+    let width = row.len();
+    let truncated = || invalid_data("truncated Radiance image".into());
+    let rle = can_encode(width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && data[2] & 0x80 == 0;
+
+    if !rle {
+        let bytes = data.get(..4 * width).ok_or_else(truncated)?;
+        for (pixel, chunk) in row.iter_mut().zip(bytes.chunks(4)) {
+            pixel.copy_from_slice(chunk);
+        }
+        return Ok(&data[4 * width..]);
+    }
+
+    if usize::from(data[2]) << 8 | usize::from(data[3]) != width {
+        return Err(invalid_data("wrong Radiance scanline width".into()));
+    }
+
+    let mut pos = 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = usize::from(*data.get(pos).ok_or_else(truncated)?);
+            pos += 1;
+            if count > 128 {
+                let value = *data.get(pos).ok_or_else(truncated)?;
+                pos += 1;
+                let run = row.get_mut(x..x + count - 128)
+                    .ok_or_else(|| invalid_data("overlong run".into()))?;
+                for pixel in run {
+                    pixel[channel] = value;
+                }
+                x += count - 128;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err(invalid_data("bad run length".into()));
+                }
+                let values = data.get(pos..pos + count)
+                    .ok_or_else(truncated)?;
+                for (pixel, &value) in row[x..].iter_mut().zip(values) {
+                    pixel[channel] = value;
+                }
+                pos += count;
+                x += count;
+            }
+        }
+    }
+
+    Ok(&data[pos..])
+}
+
+// A shared exponent and three 8-bit mantissas.
+pub fn to_rgbe(c: Color) -> [u8; 4] {
+    let (r, g, b) = (c.r.max(0.0), c.g.max(0.0), c.b.max(0.0));
+    let v = r.max(g).max(b);
+    if v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // v = m * 2^e, with m in [0.5, 1).
+    let mut e = v.log2().floor() as i32 + 1;
+    if v / 2f32.powi(e) >= 1.0 {
+        e += 1;
+    } else if v / 2f32.powi(e) < 0.5 {
+        e -= 1;
+    }
+    let scale = 256.0 / 2f32.powi(e);
+
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (e + 128).clamp(0, 255) as u8,
+    ]
+}
+
+pub fn from_rgbe(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::black();
+    }
+
+    // Each mantissa stands for the middle of the range it was cut from.
+    let scale = 2f32.powi(i32::from(rgbe[3]) - 128 - 8);
+    Color::new(
+        (f32::from(rgbe[0]) + 0.5) * scale,
+        (f32::from(rgbe[1]) + 0.5) * scale,
+        (f32::from(rgbe[2]) + 0.5) * scale,
+    )
+}
+
+// Runs of 3 or more equal bytes are written as 128 + length followed by the
+// byte; everything else as literal spans of a length followed by the bytes.
+fn encode_runs(values: &[u8], out: &mut Vec<u8>) {
+    const MIN_RUN: usize = 3;
+    const MAX_RUN: usize = 127;
+    const MAX_SPAN: usize = 128;
+
+    let run_at = |i: usize| {
+        values[i..].iter()
+            .take(MAX_RUN)
+            .take_while(|&&v| v == values[i])
+            .count()
+    };
+
+    let mut i = 0;
+    while i < values.len() {
+        let run = run_at(i);
+        if run >= MIN_RUN {
+            out.push(128 + run as u8);
+            out.push(values[i]);
+            i += run;
+            continue;
+        }
+
+        // Extend the literal span up to the start of the next run.
+        let start = i;
+        while i < values.len() && i - start < MAX_SPAN && run_at(i) < MIN_RUN {
+            i += 1;
+        }
+        out.push((i - start) as u8);
+        out.extend(&values[start..i]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(hdr: &[u8]) -> (usize, usize, Vec<[u8; 4]>) {
+        let text = String::from_utf8_lossy(hdr);
+        let header_end = text.find("\n\n").unwrap() + 2;
+        let size_end = header_end + text[header_end..].find('\n').unwrap();
+        let size: Vec<&str> = text[header_end..size_end].split(' ').collect();
+        assert_eq!((size[0], size[2]), ("-Y", "+X"));
+        let height: usize = size[1].parse().unwrap();
+        let width: usize = size[3].parse().unwrap();
+
+        let mut pos = size_end + 1;
+        let mut pixels = vec![];
+        for _ in 0..height {
+            if width < 8 {
+                for chunk in hdr[pos..pos + 4 * width].chunks(4) {
+                    pixels.push([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                }
+                pos += 4 * width;
+                continue;
+            }
+
+            assert_eq!(&hdr[pos..pos + 4], &[2, 2, (width >> 8) as u8,
+                                              width as u8]);
+            pos += 4;
+            let mut row = vec![[0u8; 4]; width];
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = hdr[pos] as usize;
+                    pos += 1;
+                    if count > 128 {
+                        for pixel in &mut row[x..x + count - 128] {
+                            pixel[channel] = hdr[pos];
+                        }
+                        x += count - 128;
+                        pos += 1;
+                    } else {
+                        for pixel in &mut row[x..x + count] {
+                            pixel[channel] = hdr[pos];
+                            pos += 1;
+                        }
+                        x += count;
+                    }
+                }
+                assert_eq!(x, width);
+            }
+            pixels.extend(row);
+        }
+        assert_eq!(pos, hdr.len());
+
+        (width, height, pixels)
+    }
+
+    fn round_trip(width: usize, height: usize) {
+        let pixels: Vec<Color> = (0..width * height)
+            .map(|i| match i % 7 {
+                0..=2 => Color::new(0.5, 0.5, 0.5),
+                3 => Color::new(1000.0, 0.001, 3.0),
+                4 => Color::black(),
+                _ => Color::new(i as f32 / 10.0, 0.2, 1.0 / (i + 1) as f32),
+            })
+            .collect();
+
+        let mut hdr = vec![];
+        write_hdr(&mut hdr, width, height, &pixels).unwrap();
+        let (w, h, decoded) = decode(&hdr);
+
+        assert_eq!((w, h), (width, height));
+        for (&c, &rgbe) in pixels.iter().zip(&decoded) {
+            assert_eq!(rgbe, to_rgbe(c));
+
+            // RGBE keeps about 8 bits relative to the brightest channel.
+            let d = from_rgbe(rgbe);
+            let brightest = c.r.max(c.g).max(c.b);
+            for &(a, b) in &[(c.r, d.r), (c.g, d.g), (c.b, d.b)] {
+                assert!((a - b).abs() <= brightest / 128.0 + 1e-30,
+                        "{:?} came back as {:?}", c, d);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(64, 3);
+        round_trip(300, 2);
+        round_trip(5, 4);
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        for &(width, height) in &[(64, 3), (5, 4)] {
+            let pixels: Vec<Color> = (0..width * height)
+                .map(|i| match i % 3 {
+                    0 => Color::new(0.5, 0.25, 2.0),
+                    1 => Color::black(),
+                    _ => Color::new(100.0, 1.0, 0.01 * i as f32),
+                })
+                .collect();
+            let mut hdr = vec![];
+            write_hdr(&mut hdr, width, height, &pixels).unwrap();
+
+            let image = read_hdr(&hdr).unwrap();
+            assert_eq!((image.width(), image.height()), (width, height));
+            for (&c, &d) in pixels.iter().zip(image.pixels()) {
+                assert_eq!(d, from_rgbe(to_rgbe(c)));
+            }
+
+            assert!(read_hdr(&hdr[..hdr.len() - 1]).is_err());
+        }
+
+        assert!(read_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n\0\0\0\0").is_err());
+        // Sizes far too big for the data, or to count.
+        assert!(read_hdr(b"#?RADIANCE\n\n-Y 99999999 +X 100\n\0").is_err());
+        let huge = format!("#?RADIANCE\n\n-Y 1 +X {}\n", usize::MAX);
+        assert!(read_hdr(huge.as_bytes()).is_err());
+        assert!(read_hdr(b"P3\n").is_err());
+    }
+
+    #[test]
+    fn rgbe() {
+        assert_eq!(to_rgbe(Color::new(1.0, 0.5, 0.0)), [128, 64, 0, 129]);
+        assert_eq!(to_rgbe(Color::black()), [0, 0, 0, 0]);
+        assert_eq!(from_rgbe([0, 0, 0, 0]), Color::black());
+    }
+}
@@ -0,0 +1,134 @@
+// image/mod.rs - Images, and reading and writing them.
+// Written by quadfault
+// 10/18/26
+
+mod deflate;
+mod exr;
+mod hdr;
+mod pfm;
+mod png;
+mod ppm;
+
+pub use self::exr::*;
+pub use self::hdr::*;
+pub use self::pfm::*;
+pub use self::png::*;
+pub use self::ppm::*;
+
+use std::fs;
+use std::io::{ self, Write };
+use std::path::Path;
+
+use crate::math::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Pfm,
+    Hdr,
+    Exr,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("pfm") => Ok(ImageFormat::Pfm),
+            Some("hdr") => Ok(ImageFormat::Hdr),
+            Some("exr") => Ok(ImageFormat::Exr),
+            Some(other) => Err(format!(
+                "unsupported output format '.{}' (expected .ppm, .png, \
+                 .pfm, .hdr or .exr)",
+                other
+            )),
+            None => Err(format!(
+                "can't tell the output format of '{}' without an extension",
+                path.display()
+            )),
+        }
+    }
+}
+
+// A linear image, with pixels row by row from the top left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height,
+                   "wrong number of pixels for a {}x{} image", width, height);
+
+        Self { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    // (0, 0) is the top left.
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+pub trait ImageWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()>;
+}
+
+impl ImageFormat {
+    // The writer for this format, with its usual settings.
+    pub fn writer(self) -> Box<dyn ImageWriter> {
+        match self {
+            ImageFormat::Ppm => Box::new(PpmWriter),
+            ImageFormat::Png => Box::new(PngWriter {
+                format: PngFormat::Rgb8,
+            }),
+            ImageFormat::Pfm => Box::new(PfmWriter),
+            ImageFormat::Hdr => Box::new(HdrWriter),
+            ImageFormat::Exr => Box::new(ExrWriter {
+                pixel_type: ExrPixelType::Half,
+            }),
+        }
+    }
+}
+
+// Reads a linear image from a .hdr or .pfm file.
+pub fn read_image(path: &Path) -> io::Result<Image> {
+    match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Hdr) => read_hdr(&fs::read(path)?),
+        Ok(ImageFormat::Pfm) => read_pfm(&fs::read(path)?),
+        _ => Err(invalid_data(
+            "can only read .hdr and .pfm images".into()
+        )),
+    }
+}
+
+fn invalid_data(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+// Gamma corrects a linear value and quantises it to an integer from 0 to
+// `max`. Gamma 2 (a square root) is close enough to sRGB.
+fn quantize(linear: f32, max: u16) -> u16 {
+    let max = f32::from(max);
+
+    ((max + 0.99) * linear.max(0.0).sqrt()).min(max) as u16
+}
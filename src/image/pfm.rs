@@ -0,0 +1,166 @@
+// image/pfm.rs - Portable float map images.
+// Written by quadfault
+// 10/18/26
+
+use std::io::{ self, Write };
+use std::str::FromStr;
+
+use crate::math::Color;
+
+use super::{ Image, ImageWriter, invalid_data };
+
+// Linear colour PFM images.
+pub struct PfmWriter;
+
+impl ImageWriter for PfmWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        write_pfm(out, image.width(), image.height(), image.pixels())
+    }
+}
+
+// Writes linear colours, row by row from the top left, as a colour PFM
+// image. PFM stores its rows from the bottom up; a negative scale marks the
+// floats as little-endian.
+pub fn write_pfm(out: &mut dyn Write,
+                 width: usize,
+                 height: usize,
+                 pixels: &[Color])
+    -> io::Result<()>
+{
+    assert_eq!(pixels.len(), width * height,
+               "wrong number of pixels for a {}x{} image", width, height);
+
+    write!(out, "PF\n{} {}\n-1.0\n", width, height)?;
+
+    for row in pixels.chunks(width).rev() {
+        let mut bytes = Vec::with_capacity(width * 12);
+        for c in row {
+            bytes.extend(&c.r.to_le_bytes());
+            bytes.extend(&c.g.to_le_bytes());
+            bytes.extend(&c.b.to_le_bytes());
+        }
+        out.write_all(&bytes)?;
+    }
+
+    out.flush()
+}
+
+// Reads a colour or greyscale PFM image, in either byte order. The scale
+// factor is only used for its sign.
+pub fn read_pfm(input: &[u8]) -> io::Result<Image> {
+    // The header is four whitespace-separated fields, the last followed by
+    // a single whitespace character.
+    let mut fields = vec![];
+    let mut pos = 0;
+    while fields.len() < 4 {
+        while input.get(pos).is_some_and(u8::is_ascii_whitespace) {
+            pos += 1;
+        }
+        let start = pos;
+        while input.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(invalid_data("truncated PFM header".into()));
+        }
+        fields.push(String::from_utf8_lossy(&input[start..pos]).into_owned());
+    }
+    pos += 1;
+
+    let channels = match fields[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err(invalid_data("not a PFM image".into())),
+    };
+    let width: usize = header_field(&fields[1])?;
+    let height: usize = header_field(&fields[2])?;
+    let scale: f32 = header_field(&fields[3])?;
+    if width == 0 || height == 0 || scale == 0.0 {
+        return Err(invalid_data("invalid PFM header".into()));
+    }
+
+    // The size is checked against the input before anything is allocated,
+    // so a corrupt header can't ask for more memory than the file holds.
+    let end = width.checked_mul(height)
+        .and_then(|n| n.checked_mul(channels * 4))
+        .and_then(|size| pos.checked_add(size))
+        .ok_or_else(|| invalid_data("PFM image too big".into()))?;
+    let data = input.get(pos..end)
+        .ok_or_else(|| invalid_data("truncated PFM image".into()))?;
+    let floats: Vec<f32> = data.chunks(4)
+        .map(|b| {
+            let bytes = [b[0], b[1], b[2], b[3]];
+            if scale < 0.0 {
+                f32::from_le_bytes(bytes)
+            } else {
+                f32::from_be_bytes(bytes)
+            }
+        })
+        .collect();
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in floats.chunks(width * channels).rev() {
+        pixels.extend(row.chunks(channels).map(|c| match *c {
+            [r, g, b] => Color::new(r, g, b),
+            _ => Color::new(c[0], c[0], c[0]),
+        }));
+    }
+
+    Ok(Image::new(width, height, pixels))
+}
+
+fn header_field<T: FromStr>(field: &str) -> io::Result<T> {
+    field.parse().map_err(|_| {
+        invalid_data(format!("invalid PFM header field '{}'", field))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let pixels = [
+            Color::new(1.0, 2.0, 3.0),
+            Color::new(0.5, 0.0, 0.0),
+            Color::new(0.0, 0.0, 100.0),
+            Color::new(-1.0, 0.25, 0.0),
+        ];
+        let mut pfm = vec![];
+        write_pfm(&mut pfm, 2, 2, &pixels).unwrap();
+
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&pfm[..header.len()], header);
+
+        let floats: Vec<f32> = pfm[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(floats, vec![
+            0.0, 0.0, 100.0, -1.0, 0.25, 0.0,
+            1.0, 2.0, 3.0, 0.5, 0.0, 0.0,
+        ]);
+
+        let image = read_pfm(&pfm).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.pixels(), &pixels[..]);
+    }
+
+    #[test]
+    fn greyscale_and_big_endian() {
+        let mut pfm = b"Pf\n1 2\n1.0\n".to_vec();
+        pfm.extend(&0.5f32.to_be_bytes());
+        pfm.extend(&4.0f32.to_be_bytes());
+
+        let image = read_pfm(&pfm).unwrap();
+        assert_eq!(image.get(0, 0), Color::new(4.0, 4.0, 4.0));
+        assert_eq!(image.get(0, 1), Color::new(0.5, 0.5, 0.5));
+
+        assert!(read_pfm(&pfm[..pfm.len() - 1]).is_err());
+        assert!(read_pfm(b"P6\n1 1\n255\n").is_err());
+        let huge = format!("PF\n{} {}\n-1.0\n", usize::MAX, 2);
+        assert!(read_pfm(huge.as_bytes()).is_err());
+        assert!(read_pfm(b"PF\n100000 100000\n-1.0\n\0\0\0\0").is_err());
+    }
+}
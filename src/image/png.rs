@@ -0,0 +1,317 @@
+// image/png.rs - PNG images.
+// Written by quadfault
+// 10/18/26
+
+use std::io::{ self, Write };
+
+use super::{ Image, ImageWriter, quantize };
+use super::deflate::zlib_compress;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PngFormat {
+    Rgb8,
+    Rgba8,
+    Rgb16,
+    Rgba16,
+}
+
+impl PngFormat {
+    pub fn channels(self) -> usize {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgb16 => 3,
+            PngFormat::Rgba8 | PngFormat::Rgba16 => 4,
+        }
+    }
+
+    pub fn bit_depth(self) -> u8 {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgba8 => 8,
+            PngFormat::Rgb16 | PngFormat::Rgba16 => 16,
+        }
+    }
+
+    fn color_type(self) -> u8 {
+        match self.channels() {
+            3 => 2,
+            _ => 6,
+        }
+    }
+
+    fn bytes_per_pixel(self) -> usize {
+        self.channels() * usize::from(self.bit_depth() / 8)
+    }
+}
+
+// Gamma-corrected PNG images, with an opaque alpha channel for the RGBA
+// formats.
+pub struct PngWriter {
+    pub format: PngFormat,
+}
+
+impl ImageWriter for PngWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        let max = match self.format.bit_depth() {
+            8 => 255,
+            _ => 65535,
+        };
+        let alpha = self.format.channels() == 4;
+
+        let mut samples = Vec::with_capacity(
+            image.pixels().len() * self.format.channels()
+        );
+        for c in image.pixels() {
+            samples.push(quantize(c.r, max));
+            samples.push(quantize(c.g, max));
+            samples.push(quantize(c.b, max));
+            if alpha {
+                samples.push(max);
+            }
+        }
+
+        write_png(out, image.width(), image.height(), self.format, &samples)
+    }
+}
+
+// Writes a PNG image. `samples` holds the channels of each pixel, row by row
+// from the top left, each up to 255 for 8-bit formats or 65535 for 16-bit
+// ones.
+pub fn write_png(out: &mut dyn Write,
+                 width: usize,
+                 height: usize,
+                 format: PngFormat,
+                 samples: &[u16])
+    -> io::Result<()>
+{
+    assert!(width > 0 && height > 0, "PNG images can't be empty");
+    assert_eq!(samples.len(), width * height * format.channels(),
+               "wrong number of samples for a {}x{} image", width, height);
+
+    let mut header = vec![];
+    header.extend(&(width as u32).to_be_bytes());
+    header.extend(&(height as u32).to_be_bytes());
+    // Bit depth, colour type, then deflate, adaptive filtering and no
+    // interlacing.
+    header.extend(&[format.bit_depth(), format.color_type(), 0, 0, 0]);
+
+    out.write_all(&SIGNATURE)?;
+    write_chunk(out, b"IHDR", &header)?;
+    write_chunk(out, b"IDAT", &zlib_compress(&filter(width, format, samples)))?;
+    write_chunk(out, b"IEND", &[])?;
+
+    out.flush()
+}
+
+fn write_chunk(out: &mut dyn Write, kind: &[u8; 4], data: &[u8])
+    -> io::Result<()>
+{
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let crc = crc32(&[&kind[..], data]);
+    out.write_all(&crc.to_be_bytes())
+}
+
+// Serialises the samples into scanlines, each preceded by the filter that
+// predicts it best by the usual heuristic: the smallest sum of absolute
+// differences.
+fn filter(width: usize, format: PngFormat, samples: &[u16]) -> Vec<u8> {
+    let bpp = format.bytes_per_pixel();
+    let row_len = width * bpp;
+    let raw: Vec<u8> = match format.bit_depth() {
+        8 => samples.iter().map(|&s| s as u8).collect(),
+        _ => samples.iter().flat_map(|s| s.to_be_bytes()).collect(),
+    };
+
+    let mut out = Vec::with_capacity(raw.len() + raw.len() / row_len);
+    let zeros = vec![0; row_len];
+    let mut candidates = vec![vec![0u8; row_len]; 5];
+
+    for (y, row) in raw.chunks(row_len).enumerate() {
+        let up = if y == 0 { &zeros[..] } else {
+            &raw[(y - 1) * row_len..y * row_len]
+        };
+
+        for i in 0..row_len {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = up[i];
+            let c = if i >= bpp { up[i - bpp] } else { 0 };
+
+            candidates[0][i] = row[i];
+            candidates[1][i] = row[i].wrapping_sub(a);
+            candidates[2][i] = row[i].wrapping_sub(b);
+            candidates[3][i] =
+                row[i].wrapping_sub(((u16::from(a) + u16::from(b)) / 2) as u8);
+            candidates[4][i] = row[i].wrapping_sub(paeth(a, b, c));
+        }
+
+        let cost = |filtered: &Vec<u8>| -> u64 {
+            filtered.iter().map(|&x| u64::from((x as i8).unsigned_abs())).sum()
+        };
+        let best = (0..5).min_by_key(|&f| cost(&candidates[f])).unwrap_or(0);
+
+        out.push(best as u8);
+        out.extend(&candidates[best]);
+    }
+
+    out
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let pa = (p - i16::from(a)).abs();
+    let pb = (p - i16::from(b)).abs();
+    let pc = (p - i16::from(c)).abs();
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// The CRC-32 of the concatenation of `parts`, as in zlib and PNG.
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+
+    let mut crc = 0xffffffff;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc = table[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8);
+    }
+
+    crc ^ 0xffffffff
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use super::super::deflate::zlib_decompress;
+
+    // Decodes the PNGs that write_png writes, checking every CRC, and returns
+    // the width, height, format and samples.
+    fn decode(png: &[u8]) -> (usize, usize, PngFormat, Vec<u16>) {
+        assert_eq!(&png[..8], &SIGNATURE);
+
+        let mut pos = 8;
+        let mut header = vec![];
+        let mut idat = vec![];
+        loop {
+            let len = u32::from_be_bytes([
+                png[pos], png[pos + 1], png[pos + 2], png[pos + 3],
+            ]) as usize;
+            let kind = &png[pos + 4..pos + 8];
+            let data = &png[pos + 8..pos + 8 + len];
+            let crc = &png[pos + 8 + len..pos + 12 + len];
+            assert_eq!(crc, crc32(&[kind, data]).to_be_bytes());
+            pos += 12 + len;
+
+            match kind {
+                b"IHDR" => header = data.to_vec(),
+                b"IDAT" => idat.extend(data),
+                b"IEND" => break,
+                _ => panic!("unexpected chunk"),
+            }
+        }
+        assert_eq!(pos, png.len());
+
+        let width = u32::from_be_bytes([
+            header[0], header[1], header[2], header[3],
+        ]) as usize;
+        let height = u32::from_be_bytes([
+            header[4], header[5], header[6], header[7],
+        ]) as usize;
+        let format = match (header[8], header[9]) {
+            (8, 2) => PngFormat::Rgb8,
+            (8, 6) => PngFormat::Rgba8,
+            (16, 2) => PngFormat::Rgb16,
+            (16, 6) => PngFormat::Rgba16,
+            other => panic!("unexpected depth and colour type {:?}", other),
+        };
+
+        let filtered = zlib_decompress(&idat).unwrap();
+        let bpp = format.bytes_per_pixel();
+        let row_len = width * bpp;
+        let mut raw: Vec<u8> = vec![];
+        for y in 0..height {
+            let line = &filtered[y * (row_len + 1)..(y + 1) * (row_len + 1)];
+            let start = raw.len();
+            for i in 0..row_len {
+                let a = if i >= bpp { raw[start + i - bpp] } else { 0 };
+                let b = if y > 0 { raw[start + i - row_len] } else { 0 };
+                let c = if y > 0 && i >= bpp {
+                    raw[start + i - row_len - bpp]
+                } else {
+                    0
+                };
+                let predicted = match line[0] {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+                    4 => paeth(a, b, c),
+                    f => panic!("bad filter type {}", f),
+                };
+                raw.push(line[i + 1].wrapping_add(predicted));
+            }
+        }
+
+        let samples = match format.bit_depth() {
+            8 => raw.iter().map(|&x| u16::from(x)).collect(),
+            _ => raw.chunks(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect(),
+        };
+
+        (width, height, format, samples)
+    }
+
+    fn round_trip(width: usize, height: usize, format: PngFormat) {
+        let mut rng = StdRng::from_seed([5; 32]);
+        let max = if format.bit_depth() == 8 { 255 } else { 65535 };
+
+        // A smooth gradient with some noise, so every filter gets a chance.
+        let samples: Vec<u16> = (0..width * height * format.channels())
+            .map(|i| {
+                let x = (i * 7 % 251) as u32 * max / 250;
+                if rng.gen_range(0, 10) == 0 {
+                    rng.gen_range(0, max)
+                } else {
+                    x
+                }
+            })
+            .map(|x| x as u16)
+            .collect();
+
+        let mut png = vec![];
+        write_png(&mut png, width, height, format, &samples).unwrap();
+
+        assert_eq!(decode(&png), (width, height, format, samples));
+    }
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(crc32(&[b"IEND"]), 0xae426082);
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(37, 23, PngFormat::Rgb8);
+        round_trip(16, 9, PngFormat::Rgba8);
+        round_trip(20, 31, PngFormat::Rgb16);
+        round_trip(5, 4, PngFormat::Rgba16);
+        round_trip(1, 1, PngFormat::Rgb8);
+    }
+}
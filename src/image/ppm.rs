@@ -0,0 +1,44 @@
+// image/ppm.rs - Plain PPM images.
+// Written by quadfault
+// 10/18/26
+
+use std::io::{ self, Write };
+
+use super::{ Image, ImageWriter, quantize };
+
+// 8-bit, gamma-corrected plain PPM images.
+pub struct PpmWriter;
+
+impl ImageWriter for PpmWriter {
+    fn write(&self, image: &Image, out: &mut dyn Write) -> io::Result<()> {
+        let samples: Vec<u8> = image.pixels()
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b])
+            .map(|x| quantize(x, 255) as u8)
+            .collect();
+
+        write_ppm(out, image.width(), image.height(), &samples)
+    }
+}
+
+// Writes an ASCII ("P3") PPM image. `samples` holds the red, green and blue
+// of each pixel, row by row from the top left.
+pub fn write_ppm(out: &mut dyn Write,
+                 width: usize,
+                 height: usize,
+                 samples: &[u8])
+    -> io::Result<()>
+{
+    assert_eq!(samples.len(), width * height * 3,
+               "wrong number of samples for a {}x{} image", width, height);
+
+    writeln!(out, "P3")?;
+    writeln!(out, "{} {}", width, height)?;
+    writeln!(out, "255")?;
+
+    for pixel in samples.chunks(3) {
+        writeln!(out, "{} {} {}", pixel[0], pixel[1], pixel[2])?;
+    }
+
+    out.flush()
+}
@@ -0,0 +1,27 @@
+// lib.rs - A raytracer, as a library.
+// Written by quadfault
+// 10/18/26
+
+pub mod backgrounds;
+pub mod cameras;
+pub mod film;
+pub mod filters;
+pub mod image;
+pub mod lights;
+pub mod loaders;
+pub mod materials;
+pub mod math;
+pub mod models;
+pub mod samplers;
+pub mod scene;
+
+mod tile;
+
+pub use self::backgrounds::Sky;
+pub use self::scene::{
+    AdaptiveSampling,
+    Progress,
+    Progressive,
+    Scene,
+    SceneBuilder,
+};
@@ -0,0 +1,55 @@
+// lights/directional_light.rs - Directional lights.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Point, Vector };
+
+use super::{ Illumination, Light };
+
+// Shines the same everywhere from one direction, like the sun: light
+// infinitely far away travelling along `direction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionalLight {
+    direction: Vector,
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, irradiance: Color) -> Self {
+        Self {
+            direction: direction.hat(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn illuminate(&self, _point: Point) -> Option<Illumination> {
+        Some(Illumination {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+            irradiance: self.irradiance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shines_from_one_direction() {
+        let sun = DirectionalLight::new(
+            Vector::new(0.0, -3.0, 0.0),
+            Color::new(1.0, 1.0, 0.5),
+        );
+
+        for &point in &[Point::origin(), Point::new(100.0, -5.0, 2.0)] {
+            let il = sun.illuminate(point).unwrap();
+
+            assert_eq!(il.direction, Vector::new(0.0, 1.0, 0.0));
+            assert_eq!(il.distance, f64::INFINITY);
+            assert_eq!(il.irradiance, Color::new(1.0, 1.0, 0.5));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// lights/mod.rs - Punctual lights, which shine from a single point or
+// direction rather than from a model.
+// Written by quadfault
+// 10/18/26
+
+mod directional_light;
+mod point_light;
+mod spot_light;
+
+pub use self::directional_light::*;
+pub use self::point_light::*;
+pub use self::spot_light::*;
+
+use crate::math::{ Color, Point, Vector };
+
+// The light arriving at a point from a punctual light.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Illumination {
+    // A unit vector from the point towards the light.
+    pub direction: Vector,
+    // How far away the light is: infinitely, for directional lights.
+    pub distance: f64,
+    // The light arriving per unit area facing the light.
+    pub irradiance: Color,
+}
+
+// Punctual lights have no size, so rays never hit them; they only light
+// what shadow rays find nothing in the way of.
+pub trait Light: Send + Sync {
+    // The light reaching `point`, whatever might be in the way. None if the
+    // light doesn't shine there.
+    fn illuminate(&self, point: Point) -> Option<Illumination>;
+}
@@ -0,0 +1,60 @@
+// lights/point_light.rs - Point lights.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Point };
+
+use super::{ Illumination, Light };
+
+// Shines equally in every direction from one point, falling off with the
+// square of the distance. `intensity` is the irradiance a unit away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    position: Point,
+    intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self { position, intensity }
+    }
+}
+
+impl Light for PointLight {
+    fn illuminate(&self, point: Point) -> Option<Illumination> {
+        let to_light = self.position - point;
+        let distance_sqr = to_light.norm_sqr();
+        if distance_sqr == 0.0 {
+            return None;
+        }
+
+        let distance = distance_sqr.sqrt();
+
+        Some(Illumination {
+            direction: to_light / distance,
+            distance,
+            irradiance: self.intensity * (1.0 / distance_sqr) as f32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Vector;
+
+    use super::*;
+
+    #[test]
+    fn falls_off_with_distance() {
+        let light = PointLight::new(
+            Point::new(0.0, 2.0, 0.0),
+            Color::new(8.0, 4.0, 0.0),
+        );
+        let il = light.illuminate(Point::origin()).unwrap();
+
+        assert_eq!(il.direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(il.distance, 2.0);
+        assert_eq!(il.irradiance, Color::new(2.0, 1.0, 0.0));
+        assert!(light.illuminate(Point::new(0.0, 2.0, 0.0)).is_none());
+    }
+}
@@ -0,0 +1,93 @@
+// lights/spot_light.rs - Spot lights.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Point, Vector };
+
+use super::{ Illumination, Light, PointLight };
+
+// A point light that only shines within a cone around the direction it
+// points. It's at full intensity within `inner` degrees of the axis and
+// fades out smoothly by `outer` degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotLight {
+    light: PointLight,
+    direction: Vector,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    // An inner angle wider than the outer one is narrowed to match it.
+    pub fn new(position: Point,
+               target: Point,
+               intensity: Color,
+               inner: f64,
+               outer: f64)
+        -> Self
+    {
+        Self {
+            light: PointLight::new(position, intensity),
+            direction: (target - position).hat(),
+            cos_inner: inner.min(outer).to_radians().cos(),
+            cos_outer: outer.to_radians().cos(),
+        }
+    }
+
+    // How much of the light's intensity shines along `direction`, a unit
+    // vector from the light.
+    fn falloff(&self, direction: Vector) -> f64 {
+        let cosine = direction.dot(self.direction);
+        if cosine >= self.cos_inner {
+            return 1.0;
+        }
+        if cosine <= self.cos_outer {
+            return 0.0;
+        }
+
+        let t = (cosine - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn illuminate(&self, point: Point) -> Option<Illumination> {
+        let il = self.light.illuminate(point)?;
+        let falloff = self.falloff(-il.direction);
+        if falloff == 0.0 {
+            return None;
+        }
+
+        Some(Illumination {
+            irradiance: il.irradiance * falloff as f32,
+            ..il
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_out_between_the_cones() {
+        let spot = SpotLight::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::origin(),
+            Color::new(1.0, 1.0, 1.0),
+            30.0,
+            45.0,
+        );
+        let at = |x: f64| {
+            spot.illuminate(Point::new(x, 0.0, 0.0))
+                .map_or(0.0, |il| il.irradiance.g * (1.0 + x * x) as f32)
+        };
+
+        // Full intensity up to 30 degrees off the axis (x = tan 30), none
+        // past 45 (x = 1), and fading in between.
+        assert_eq!(at(0.0), 1.0);
+        assert!((at(0.57) - 1.0).abs() < 1e-6);
+        assert!(at(0.7) < 1.0 && at(0.7) > at(0.9) && at(0.9) > 0.0);
+        assert_eq!(at(1.01), 0.0);
+    }
+}
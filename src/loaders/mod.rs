@@ -0,0 +1,179 @@
+// loaders/mod.rs - Loading models and scenes from files.
+// Written by quadfault
+// 10/18/26
+
+mod mtl;
+mod obj;
+mod scene_file;
+
+pub use self::mtl::*;
+pub use self::obj::*;
+pub use self::scene_file::*;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{ Path, PathBuf };
+use std::str::FromStr;
+
+use crate::materials::{
+    Dielectric,
+    DiffuseLight,
+    Lambertian,
+    Material,
+    Metal,
+};
+use crate::math::{ Color, Point, Vector };
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub file: PathBuf,
+    // Zero for errors that aren't about any one line, like a missing file.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(file: &Path, line: usize, reason: String) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line,
+            reason,
+        }
+    }
+
+    fn io(file: &Path, err: io::Error) -> Self {
+        Self::new(file, 0, err.to_string())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.file.display(), self.reason)
+        } else {
+            write!(f, "{}:{}: {}", self.file.display(), self.line, self.reason)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+// A material as read from a file. Models each own their material, so a
+// material that several models share is built once for each of them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaterialDesc {
+    Lambertian { albedo: Vector },
+    Metal { albedo: Vector, fuzz: f64 },
+    Dielectric { refractive_index: f64 },
+    DiffuseLight { emit: Color, two_sided: bool },
+}
+
+impl MaterialDesc {
+    pub fn build(&self) -> Box<dyn Material> {
+        match *self {
+            MaterialDesc::Lambertian { albedo } => {
+                Box::new(Lambertian::new(albedo))
+            }
+            MaterialDesc::Metal { albedo, fuzz } => {
+                Box::new(Metal::new(albedo, fuzz))
+            }
+            MaterialDesc::Dielectric { refractive_index } => {
+                Box::new(Dielectric::new(refractive_index))
+            }
+            MaterialDesc::DiffuseLight { emit, two_sided: false } => {
+                Box::new(DiffuseLight::new(emit))
+            }
+            MaterialDesc::DiffuseLight { emit, two_sided: true } => {
+                Box::new(DiffuseLight::two_sided(emit))
+            }
+        }
+    }
+}
+
+// One non-blank line of a line-oriented text file: a keyword followed by
+// whitespace-separated arguments. Comments run from '#' to the end of the
+// line.
+struct Statement<'a> {
+    file: &'a Path,
+    line: usize,
+    keyword: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Statement<'a> {
+    fn error(&self, reason: String) -> ParseError {
+        ParseError::new(self.file, self.line, reason)
+    }
+
+    fn expect_args(&self, min: usize, max: usize) -> Result<(), ParseError> {
+        let n = self.args.len();
+        if n < min || n > max {
+            let expected = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+
+            Err(self.error(format!(
+                "'{}' takes {} arguments, found {}", self.keyword, expected, n
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse<T: FromStr>(&self, i: usize, what: &str) -> Result<T, ParseError> {
+        let arg = self.args.get(i).ok_or_else(|| {
+            self.error(format!("'{}' is missing {}", self.keyword, what))
+        })?;
+
+        arg.parse().map_err(|_| {
+            self.error(format!("invalid {} '{}'", what, arg))
+        })
+    }
+
+    fn f64(&self, i: usize) -> Result<f64, ParseError> {
+        self.parse(i, "number")
+    }
+
+    fn vector(&self, i: usize) -> Result<Vector, ParseError> {
+        Ok(Vector::new(self.f64(i)?, self.f64(i + 1)?, self.f64(i + 2)?))
+    }
+
+    fn point(&self, i: usize) -> Result<Point, ParseError> {
+        Ok(Point::new(self.f64(i)?, self.f64(i + 1)?, self.f64(i + 2)?))
+    }
+
+    // Everything after the keyword, for names that may contain spaces.
+    fn rest(&self) -> Result<String, ParseError> {
+        if self.args.is_empty() {
+            Err(self.error(format!("'{}' is missing a name", self.keyword)))
+        } else {
+            Ok(self.args.join(" "))
+        }
+    }
+}
+
+fn statements<'a>(file: &'a Path, text: &'a str)
+    -> impl Iterator<Item=Statement<'a>>
+{
+    text.lines().enumerate().filter_map(move |(i, line)| {
+        let line_text = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let mut words = line_text.split_whitespace();
+
+        words.next().map(|keyword| Statement {
+            file,
+            line: i + 1,
+            keyword,
+            args: words.collect(),
+        })
+    })
+}
+
+fn read_file(path: &Path) -> Result<String, ParseError> {
+    std::fs::read_to_string(path).map_err(|err| ParseError::io(path, err))
+}
@@ -0,0 +1,225 @@
+// loaders/mtl.rs - Wavefront material libraries (.mtl files.)
+// Written by quadfault
+// 10/18/26
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::math::{ Color, Vector };
+
+use super::{ MaterialDesc, ParseError, Statement, read_file, statements };
+
+// The parts of an MTL material that map onto our materials.
+struct MtlEntry {
+    name: String,
+    line: usize,
+    kd: Vector,
+    ks: Vector,
+    ke: Vector,
+    ns: f64,
+    ni: f64,
+    dissolve: f64,
+    illum: u32,
+}
+
+pub fn load_mtl<P: AsRef<Path>>(path: P)
+    -> Result<HashMap<String, MaterialDesc>, ParseError>
+{
+    let path = path.as_ref();
+
+    parse_mtl(path, &read_file(path)?)
+}
+
+// Materials with an emissive colour become DiffuseLight; ones that are
+// transparent (by dissolve or illumination model) become Dielectric; ones
+// with mirror reflection turned on become Metal, with specular exponents
+// mapped onto fuzz; everything else is Lambertian.
+pub fn parse_mtl(path: &Path, text: &str)
+    -> Result<HashMap<String, MaterialDesc>, ParseError>
+{
+    let mut materials = HashMap::new();
+    let mut current: Option<MtlEntry> = None;
+
+    for st in statements(path, text) {
+        if st.keyword == "newmtl" {
+            if let Some(entry) = current.take() {
+                add(&mut materials, path, entry)?;
+            }
+            current = Some(MtlEntry {
+                name: st.rest()?,
+                line: st.line,
+                kd: Vector::new(0.8, 0.8, 0.8),
+                ks: Vector::zero(),
+                ke: Vector::zero(),
+                ns: 0.0,
+                ni: 1.5,
+                dissolve: 1.0,
+                illum: 2,
+            });
+            continue;
+        }
+
+        let entry = match current.as_mut() {
+            Some(entry) => entry,
+            None => return Err(st.error(format!(
+                "'{}' before any 'newmtl'", st.keyword
+            ))),
+        };
+        match st.keyword {
+            "Kd" => entry.kd = color(&st)?,
+            "Ks" => entry.ks = color(&st)?,
+            "Ke" => entry.ke = color(&st)?,
+            "Ns" => {
+                st.expect_args(1, 1)?;
+                entry.ns = st.f64(0)?;
+            }
+            "Ni" => {
+                st.expect_args(1, 1)?;
+                entry.ni = st.f64(0)?;
+            }
+            "d" => {
+                st.expect_args(1, 1)?;
+                entry.dissolve = st.f64(0)?;
+            }
+            "Tr" => {
+                st.expect_args(1, 1)?;
+                entry.dissolve = 1.0 - st.f64(0)?;
+            }
+            "illum" => {
+                st.expect_args(1, 1)?;
+                entry.illum = st.parse(0, "illumination model")?;
+            }
+            // Ambient colour, texture maps and the rest have no counterpart.
+            _ => {}
+        }
+    }
+
+    if let Some(entry) = current {
+        add(&mut materials, path, entry)?;
+    }
+
+    Ok(materials)
+}
+
+fn add(materials: &mut HashMap<String, MaterialDesc>,
+       path: &Path,
+       entry: MtlEntry)
+    -> Result<(), ParseError>
+{
+    let ke = entry.ke;
+    let desc = if ke != Vector::zero() {
+        MaterialDesc::DiffuseLight {
+            emit: Color::new(ke.x as f32, ke.y as f32, ke.z as f32),
+            two_sided: false,
+        }
+    } else if entry.dissolve < 1.0 || [4, 6, 7, 9].contains(&entry.illum) {
+        MaterialDesc::Dielectric { refractive_index: entry.ni }
+    } else if [3, 5, 8].contains(&entry.illum) {
+        MaterialDesc::Metal {
+            albedo: entry.ks,
+            fuzz: (2.0 / (entry.ns + 2.0)).sqrt(),
+        }
+    } else {
+        MaterialDesc::Lambertian { albedo: entry.kd }
+    };
+
+    if materials.insert(entry.name.clone(), desc).is_some() {
+        return Err(ParseError::new(
+            path,
+            entry.line,
+            format!("material '{}' is defined twice", entry.name),
+        ));
+    }
+
+    Ok(())
+}
+
+// "Kd r [g b]": a single value means grey.
+fn color(st: &Statement) -> Result<Vector, ParseError> {
+    if st.args.first() == Some(&"spectral") || st.args.first() == Some(&"xyz")
+    {
+        return Err(st.error(format!(
+            "'{} {}' colors are not supported", st.keyword, st.args[0]
+        )));
+    }
+
+    st.expect_args(1, 3)?;
+    match st.args.len() {
+        1 => {
+            let v = st.f64(0)?;
+            Ok(Vector::new(v, v, v))
+        }
+        3 => st.vector(0),
+        _ => Err(st.error(format!(
+            "'{}' takes 1 or 3 numbers", st.keyword
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn materials() {
+        let text = "\
+# Exported by hand.
+newmtl red
+Kd 0.8 0.1 0.1
+illum 2
+
+newmtl chrome
+Kd 0 0 0
+Ks 0.9 0.9 0.9
+Ns 998
+illum 3
+
+newmtl glass
+Ni 1.33
+d 0.1
+
+newmtl lamp
+Kd 0.8 0.8 0.8
+Ke 10 8 6
+";
+        let materials = parse_mtl(Path::new("test.mtl"), text).unwrap();
+
+        assert_eq!(materials.len(), 4);
+        assert_eq!(
+            materials["red"],
+            MaterialDesc::Lambertian { albedo: Vector::new(0.8, 0.1, 0.1) },
+        );
+        match materials["chrome"] {
+            MaterialDesc::Metal { albedo, fuzz } => {
+                assert_eq!(albedo, Vector::new(0.9, 0.9, 0.9));
+                assert!(fuzz < 0.05);
+            }
+            ref other => panic!("chrome loaded as {:?}", other),
+        }
+        assert_eq!(
+            materials["glass"],
+            MaterialDesc::Dielectric { refractive_index: 1.33 },
+        );
+        assert_eq!(
+            materials["lamp"],
+            MaterialDesc::DiffuseLight {
+                emit: Color::new(10.0, 8.0, 6.0),
+                two_sided: false,
+            },
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let path = Path::new("bad.mtl");
+
+        let err = parse_mtl(path, "Kd 1 1 1\n").unwrap_err();
+        assert_eq!(err.line, 1);
+
+        let err = parse_mtl(path, "newmtl a\nKd 1 x 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "bad.mtl:2: invalid number 'x'");
+
+        let err = parse_mtl(path, "newmtl a\n\nnewmtl a\n").unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+}
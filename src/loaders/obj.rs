@@ -0,0 +1,334 @@
+// loaders/obj.rs - Wavefront object files (.obj files.)
+// Written by quadfault
+// 10/18/26
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::math::{ Point, Vector };
+use crate::models::{ MeshVertex, TriangleMesh };
+
+use super::{
+    MaterialDesc,
+    ParseError,
+    Statement,
+    load_mtl,
+    read_file,
+    statements,
+};
+
+// Faces that share an object, group and material end up in one mesh.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MeshKey {
+    object: String,
+    group: String,
+    material: Option<String>,
+}
+
+#[derive(Default)]
+struct ObjData {
+    positions: Vec<Point>,
+    normals: Vec<Vector>,
+    uvs: Vec<(f64, f64)>,
+    materials: HashMap<String, MaterialDesc>,
+    meshes: Vec<(MeshKey, Vec<[MeshVertex; 3]>)>,
+}
+
+pub fn load_obj<P: AsRef<Path>>(path: P)
+    -> Result<Vec<TriangleMesh>, ParseError>
+{
+    let path = path.as_ref();
+
+    parse_obj(path, &read_file(path)?)
+}
+
+// Parses the text of an OBJ file, returning one mesh per object, group and
+// material. Polygons are triangulated as fans, so they should be convex.
+// Material libraries are looked up relative to `path`.
+pub fn parse_obj(path: &Path, text: &str)
+    -> Result<Vec<TriangleMesh>, ParseError>
+{
+    let mut data = ObjData::default();
+    let mut mesh_indices = HashMap::new();
+    let mut key = MeshKey {
+        object: String::new(),
+        group: String::new(),
+        material: None,
+    };
+
+    for st in statements(path, text) {
+        match st.keyword {
+            "v" => {
+                st.expect_args(3, 7)?;
+                data.positions.push(st.point(0)?);
+            }
+            "vn" => {
+                st.expect_args(3, 3)?;
+                data.normals.push(st.vector(0)?);
+            }
+            "vt" => {
+                st.expect_args(1, 3)?;
+                let v = if st.args.len() > 1 { st.f64(1)? } else { 0.0 };
+                data.uvs.push((st.f64(0)?, v));
+            }
+            "f" => {
+                if st.args.len() < 3 {
+                    return Err(st.error(format!(
+                        "face has {} vertices, needs at least 3",
+                        st.args.len()
+                    )));
+                }
+
+                let corners = st.args.iter()
+                    .map(|arg| data.vertex(&st, arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let index = *mesh_indices.entry(key.clone()).or_insert_with(|| {
+                    data.meshes.push((key.clone(), vec![]));
+                    data.meshes.len() - 1
+                });
+                let triangles = &mut data.meshes[index].1;
+                for i in 1..corners.len() - 1 {
+                    triangles.push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            "o" => key.object = st.args.join(" "),
+            "g" => key.group = st.args.join(" "),
+            "usemtl" => {
+                let name = st.rest()?;
+                if !data.materials.contains_key(&name) {
+                    return Err(st.error(format!(
+                        "unknown material '{}'", name
+                    )));
+                }
+                key.material = Some(name);
+            }
+            "mtllib" => {
+                if st.args.is_empty() {
+                    return Err(st.error("'mtllib' needs a file name".into()));
+                }
+
+                let dir = path.parent().unwrap_or_else(|| Path::new(""));
+                for file in &st.args {
+                    data.materials.extend(load_mtl(dir.join(file))?);
+                }
+            }
+            // Smoothing groups, lines, points, curves and the like.
+            _ => {}
+        }
+    }
+
+    Ok(data.into_meshes())
+}
+
+impl ObjData {
+    // Parses a face corner: "v", "v/vt", "v//vn" or "v/vt/vn". Indices
+    // count from 1, or backwards from the most recent element if negative.
+    fn vertex(&self, st: &Statement, arg: &str)
+        -> Result<MeshVertex, ParseError>
+    {
+        let mut parts = arg.split('/');
+        let index = |part: Option<&str>, len: usize, what: &str| {
+            let part = match part {
+                Some(part) if !part.is_empty() => part,
+                _ => return Ok(None),
+            };
+
+            let i: i64 = part.parse().map_err(|_| {
+                st.error(format!("invalid {} index '{}'", what, part))
+            })?;
+            let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+            if i == 0 || resolved < 0 || resolved >= len as i64 {
+                Err(st.error(format!(
+                    "{} index {} out of range (have {})", what, i, len
+                )))
+            } else {
+                Ok(Some(resolved as usize))
+            }
+        };
+
+        let position = index(parts.next(), self.positions.len(), "vertex")?
+            .ok_or_else(|| {
+                st.error(format!("missing vertex index in '{}'", arg))
+            })?;
+        let uv = index(parts.next(), self.uvs.len(), "texture coordinate")?;
+        let normal = index(parts.next(), self.normals.len(), "normal")?;
+        if parts.next().is_some() {
+            return Err(st.error(format!("invalid face vertex '{}'", arg)));
+        }
+
+        Ok(MeshVertex { position, normal, uv })
+    }
+
+    // Gives each mesh its own compacted copy of the vertex data it uses.
+    fn into_meshes(self) -> Vec<TriangleMesh> {
+        let default_material = MaterialDesc::Lambertian {
+            albedo: Vector::new(0.8, 0.8, 0.8),
+        };
+
+        self.meshes.iter()
+            .map(|(key, triangles)| {
+                let mut positions = Remap::default();
+                let mut normals = Remap::default();
+                let mut uvs = Remap::default();
+
+                let triangles = triangles.iter()
+                    .map(|tri| {
+                        let mut tri = *tri;
+                        for v in tri.iter_mut() {
+                            v.position = positions.get(v.position);
+                            v.normal = v.normal.map(|n| normals.get(n));
+                            v.uv = v.uv.map(|uv| uvs.get(uv));
+                        }
+                        tri
+                    })
+                    .collect();
+
+                let material = match key.material {
+                    Some(ref name) => &self.materials[name],
+                    None => &default_material,
+                };
+
+                TriangleMesh::new(
+                    positions.apply(&self.positions),
+                    normals.apply(&self.normals),
+                    uvs.apply(&self.uvs),
+                    triangles,
+                    material.build(),
+                )
+            })
+            .collect()
+    }
+}
+
+// Maps indices into a shared buffer onto indices into a smaller one that
+// holds only the elements actually used.
+#[derive(Default)]
+struct Remap {
+    new_index: HashMap<usize, usize>,
+    used: Vec<usize>,
+}
+
+impl Remap {
+    fn get(&mut self, old: usize) -> usize {
+        let used = &mut self.used;
+
+        *self.new_index.entry(old).or_insert_with(|| {
+            used.push(old);
+            used.len() - 1
+        })
+    }
+
+    fn apply<T: Copy>(&self, buffer: &[T]) -> Vec<T> {
+        self.used.iter().map(|&i| buffer[i]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::math::Ray;
+    use crate::models::Model;
+
+    use super::*;
+
+    #[test]
+    fn quad_is_triangulated() {
+        let text = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+vt 0 0
+vt 1 1
+f 1/1/1 2/1/1 3/2/1 4/2/1
+";
+        let meshes = parse_obj(Path::new("quad.obj"), text).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].len(), 2);
+
+        for &(x, y) in &[(0.1, 0.8), (0.8, 0.1)] {
+            let ray = Ray::new(
+                Point::new(x, y, 1.0),
+                Vector::new(0.0, 0.0, -1.0),
+            );
+            let hr = meshes[0].hit(&ray, 0.0, f64::MAX).unwrap();
+
+            assert_eq!(hr.normal, Vector::new(0.0, 0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn groups_and_negative_indices() {
+        let text = "\
+o first
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f -3 -2 -1
+g second
+v 0 0 1
+v 1 0 1
+v 0 1 1
+f 4 5 6
+f 4//  6 5
+";
+        let meshes = parse_obj(Path::new("groups.obj"), text).unwrap();
+
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].len(), 1);
+        assert_eq!(meshes[1].len(), 2);
+    }
+
+    #[test]
+    fn materials_from_library() {
+        let dir = std::env::temp_dir().join("rt-obj-materials");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("m.mtl"), "newmtl a\nKd 1 0 0\nnewmtl b\n")
+            .unwrap();
+        fs::write(dir.join("m.obj"), "\
+mtllib m.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl a
+f 1 2 3
+usemtl b
+f 1 3 2
+usemtl a
+f 3 2 1
+").unwrap();
+
+        let meshes = load_obj(dir.join("m.obj")).unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].len(), 2);
+        assert_eq!(meshes[1].len(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let path = Path::new("bad.obj");
+        let err = |text| parse_obj(path, text).err().unwrap();
+
+        let e = err("v 0 0 0\nv 1 0 0\nf 1 2 3\n");
+        assert_eq!(e.to_string(), "bad.obj:3: vertex index 3 out of range \
+                                   (have 2)");
+
+        let e = err("v 0 0 0\nv 1 0 x\n");
+        assert_eq!(e.line, 2);
+
+        let e = err("v 0 0 0\n\nf 1 1\n");
+        assert_eq!(e.line, 3);
+
+        let e = err("usemtl missing\n");
+        assert_eq!(e.reason, "unknown material 'missing'");
+
+        let e = err("mtllib does-not-exist.mtl\n");
+        assert_eq!(e.file, Path::new("does-not-exist.mtl"));
+        assert_eq!(e.line, 0);
+
+        let e = parse_obj(path, "v 0 0 0\nf 1 1 1/2\n").err().unwrap();
+        assert!(e.reason.contains("texture coordinate"));
+    }
+}
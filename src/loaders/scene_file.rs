@@ -0,0 +1,1405 @@
+// loaders/scene_file.rs - Scene description files.
+// Written by quadfault
+// 10/18/26
+
+// A scene file is a line-oriented text file. Comments run from '#' to the
+// end of the line; every other line is a statement: a keyword followed by
+// key=value parameters, in any order. Vectors, points and colours are three
+// comma-separated numbers with no spaces, like 0,-0.5,1. For example:
+//
+//     render width=800 height=400 samples=100 max_depth=50
+//     camera type=perspective eye=0,1,2 target=0,0,-1 fov=40
+//     sky horizon=1,1,1 zenith=0.5,0.7,1
+//     material name=red type=lambertian albedo=0.8,0.3,0.3
+//     sphere center=0,0,-1 radius=0.5 material=red
+//     light type=point position=0,2,0 intensity=4,4,4
+//
+// See README.md for the full list of statements and parameters.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::backgrounds::{
+    Background,
+    ConstantBackground,
+    EnvironmentMap,
+    Sky,
+};
+use crate::cameras::{
+    Aperture,
+    Camera,
+    CubemapCamera,
+    EquirectangularCamera,
+    FisheyeCamera,
+    FisheyeProjection,
+    LookAt,
+    OrthographicCamera,
+    PerspectiveCamera,
+    Stereo,
+    StereoCamera,
+    StereoLayout,
+    ThinLens,
+    ThinLensCamera,
+};
+use crate::filters::{
+    BoxFilter,
+    Filter,
+    GaussianFilter,
+    LanczosFilter,
+    MitchellFilter,
+    TentFilter,
+};
+use crate::image::{ Image, read_image };
+use crate::lights::{ DirectionalLight, Light, PointLight, SpotLight };
+use crate::materials::Material;
+use crate::math::{ Color, Point, Vector };
+use crate::models::{ Model, Plane, Sphere, Triangle };
+use crate::samplers::{
+    HaltonSampler,
+    IndependentSampler,
+    Sampler,
+    SobolSampler,
+    StratifiedSampler,
+};
+use crate::scene::{ AdaptiveSampling, Progressive, Scene };
+
+use super::{
+    MaterialDesc,
+    ParseError,
+    Statement,
+    load_obj,
+    read_file,
+    statements,
+};
+
+// A parsed scene file. The render settings stay separate from the scene
+// until build() so that they can still be changed, say from the command
+// line.
+pub struct SceneFile {
+    pub settings: RenderSettings,
+    pub camera: CameraDesc,
+    pub filter: FilterDesc,
+    pub background: BackgroundDesc,
+    pub models: Vec<Box<dyn Model>>,
+    pub lights: Vec<Box<dyn Light>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: usize,
+    pub max_depth: i32,
+    // None to use every available core.
+    pub threads: Option<usize>,
+    // None for the samplers' default seed, 0.
+    pub seed: Option<u64>,
+    pub sampler: SamplerDesc,
+    // With a maximum, pixels take more samples than samples_per_pixel until
+    // their relative error falls below `error`.
+    pub max_samples: Option<usize>,
+    pub error: f32,
+    // Rendering progressively takes `pass_samples` samples per pixel at a
+    // time, one if only given a time limit, in seconds.
+    pub pass_samples: Option<usize>,
+    pub time_limit: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CameraDesc {
+    // The view height defaults to whatever keeps pixels square.
+    Orthographic {
+        view: LookAt,
+        view_width: f64,
+        view_height: Option<f64>,
+    },
+    // The aspect ratio defaults to the image's.
+    Perspective {
+        view: LookAt,
+        vertical_fov: f64,
+        aspect_ratio: Option<f64>,
+    },
+    ThinLens {
+        view: LookAt,
+        vertical_fov: f64,
+        aspect_ratio: Option<f64>,
+        lens: ThinLens,
+    },
+    Equirectangular { view: LookAt },
+    Fisheye {
+        view: LookAt,
+        fov: f64,
+        projection: FisheyeProjection,
+    },
+    // The faces are as tall and wide as the image is tall.
+    Cubemap { view: LookAt },
+    // Both eyes share the image.
+    Stereo {
+        view: LookAt,
+        projection: StereoProjection,
+        stereo: Stereo,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StereoProjection {
+    Perspective { vertical_fov: f64, aspect_ratio: Option<f64> },
+    Equirectangular,
+}
+
+// An environment map's image is read along with the scene file, so that a
+// missing one is reported straight away.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackgroundDesc {
+    Sky(Sky),
+    Constant(Color),
+    Environment { image: Image, rotation: f64, intensity: f32 },
+}
+
+// Radii are in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterDesc {
+    Box { radius: f64 },
+    Tent { radius: f64 },
+    Gaussian { radius: f64, sigma: f64 },
+    Mitchell { radius: f64, b: f64, c: f64 },
+    Lanczos { radius: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplerDesc {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            width: 400,
+            height: 200,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            threads: None,
+            seed: None,
+            sampler: SamplerDesc::Sobol,
+            max_samples: None,
+            error: 0.01,
+            pass_samples: None,
+            time_limit: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    // None unless rendering progressively.
+    pub fn progressive(&self) -> Option<Progressive> {
+        if self.pass_samples.is_none() && self.time_limit.is_none() {
+            return None;
+        }
+
+        Some(Progressive {
+            pass_samples: self.pass_samples.unwrap_or(1),
+            time_limit: self.time_limit.map(Duration::from_secs_f64),
+        })
+    }
+}
+
+impl SamplerDesc {
+    pub fn build(&self, seed: u64) -> Box<dyn Sampler> {
+        match *self {
+            SamplerDesc::Independent => {
+                Box::new(IndependentSampler::with_seed(seed))
+            }
+            SamplerDesc::Stratified => {
+                Box::new(StratifiedSampler::with_seed(seed))
+            }
+            SamplerDesc::Halton => Box::new(HaltonSampler::with_seed(seed)),
+            SamplerDesc::Sobol => Box::new(SobolSampler::with_seed(seed)),
+        }
+    }
+}
+
+impl FromStr for SamplerDesc {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "independent" => Ok(SamplerDesc::Independent),
+            "stratified" => Ok(SamplerDesc::Stratified),
+            "halton" => Ok(SamplerDesc::Halton),
+            "sobol" => Ok(SamplerDesc::Sobol),
+            _ => Err(format!(
+                "unknown sampler '{}' (expected independent, stratified, \
+                 halton or sobol)", s
+            )),
+        }
+    }
+}
+
+impl CameraDesc {
+    pub fn build(&self, settings: &RenderSettings) -> Box<dyn Camera> {
+        let image_aspect = settings.width as f64 / settings.height as f64;
+
+        match *self {
+            CameraDesc::Orthographic { view, view_width, view_height } => {
+                Box::new(OrthographicCamera::with_view(
+                    settings.width,
+                    settings.height,
+                    view,
+                    view_width,
+                    view_height.unwrap_or(view_width / image_aspect),
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::Perspective { view, vertical_fov, aspect_ratio } => {
+                Box::new(PerspectiveCamera::new(
+                    settings.width,
+                    settings.height,
+                    view,
+                    vertical_fov,
+                    aspect_ratio.unwrap_or(image_aspect),
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::ThinLens {
+                view,
+                vertical_fov,
+                aspect_ratio,
+                lens,
+            } => {
+                Box::new(ThinLensCamera::new(
+                    settings.width,
+                    settings.height,
+                    view,
+                    vertical_fov,
+                    aspect_ratio.unwrap_or(image_aspect),
+                    lens,
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::Equirectangular { view } => {
+                Box::new(EquirectangularCamera::new(
+                    settings.width,
+                    settings.height,
+                    view,
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::Fisheye { view, fov, projection } => {
+                Box::new(FisheyeCamera::new(
+                    settings.width,
+                    settings.height,
+                    view,
+                    fov,
+                    projection,
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::Cubemap { view } => {
+                Box::new(CubemapCamera::new(
+                    settings.height,
+                    view,
+                    settings.samples_per_pixel,
+                ))
+            }
+            CameraDesc::Stereo { view, ref projection, stereo } => {
+                let (width, height) = stereo.layout.eye_size(
+                    settings.width,
+                    settings.height,
+                );
+
+                match *projection {
+                    StereoProjection::Perspective {
+                        vertical_fov,
+                        aspect_ratio,
+                    } => Box::new(StereoCamera::perspective(
+                        width,
+                        height,
+                        view,
+                        vertical_fov,
+                        aspect_ratio.unwrap_or(width as f64 / height as f64),
+                        stereo,
+                        settings.samples_per_pixel,
+                    )),
+                    StereoProjection::Equirectangular => {
+                        Box::new(StereoCamera::equirectangular(
+                            width,
+                            height,
+                            view,
+                            stereo,
+                            settings.samples_per_pixel,
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CameraDesc {
+    // Whether the camera can make an image of the size in the settings.
+    // The cubemap's faces are as big as the image is tall, and the stereo
+    // camera splits the image evenly between the eyes. Not checked while
+    // parsing, since the size can still be changed, say from the command
+    // line.
+    pub fn check_image_size(&self, settings: &RenderSettings)
+        -> Result<(), String>
+    {
+        let (width, height) = (settings.width, settings.height);
+
+        match *self {
+            CameraDesc::Cubemap { .. } if width != 6 * height => Err(format!(
+                "a cubemap camera needs an image 6 times as wide as it is \
+                 tall, not {}x{}", width, height
+            )),
+            CameraDesc::Stereo { stereo, .. } => {
+                let (name, size) = match stereo.layout {
+                    StereoLayout::SideBySide => ("width", width),
+                    StereoLayout::OverUnder => ("height", height),
+                };
+                if size >= 2 && size % 2 == 0 {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "a stereo camera needs an even image {} of at least \
+                         2 to split between the eyes, not {}", name, size
+                    ))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FilterDesc {
+    pub fn build(&self) -> Box<dyn Filter> {
+        match *self {
+            FilterDesc::Box { radius } => Box::new(BoxFilter::new(radius)),
+            FilterDesc::Tent { radius } => Box::new(TentFilter::new(radius)),
+            FilterDesc::Gaussian { radius, sigma } => {
+                Box::new(GaussianFilter::new(radius, sigma))
+            }
+            FilterDesc::Mitchell { radius, b, c } => {
+                Box::new(MitchellFilter::new(radius, b, c))
+            }
+            FilterDesc::Lanczos { radius } => {
+                Box::new(LanczosFilter::new(radius))
+            }
+        }
+    }
+}
+
+impl BackgroundDesc {
+    // Takes the description, so that the image needn't be copied.
+    pub fn build(self) -> Box<dyn Background> {
+        match self {
+            BackgroundDesc::Sky(sky) => Box::new(sky),
+            BackgroundDesc::Constant(color) => {
+                Box::new(ConstantBackground::new(color))
+            }
+            BackgroundDesc::Environment { image, rotation, intensity } => {
+                Box::new(EnvironmentMap::new(image, rotation, intensity))
+            }
+        }
+    }
+}
+
+impl SceneFile {
+    pub fn build(self) -> Scene {
+        let seed = self.settings.seed.unwrap_or(0);
+        let mut builder = Scene::builder(self.camera.build(&self.settings))
+            .filter(self.filter.build())
+            .background(self.background.build())
+            .max_depth(self.settings.max_depth)
+            .sampler(self.settings.sampler.build(seed))
+            .models(self.models)
+            .lights(self.lights);
+        if let Some(threads) = self.settings.threads {
+            builder = builder.threads(threads);
+        }
+        if let Some(max_samples) = self.settings.max_samples {
+            builder = builder.adaptive_sampling(AdaptiveSampling {
+                max_samples,
+                threshold: self.settings.error,
+            });
+        }
+
+        builder.build()
+    }
+}
+
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<SceneFile, ParseError> {
+    let path = path.as_ref();
+
+    parse_scene(path, &read_file(path)?)
+}
+
+// Mesh and image files are looked up relative to `path`.
+pub fn parse_scene(path: &Path, text: &str)
+    -> Result<SceneFile, ParseError>
+{
+    let mut file = SceneFile {
+        settings: RenderSettings::default(),
+        camera: CameraDesc::Orthographic {
+            view: default_view(),
+            view_width: 4.0,
+            view_height: None,
+        },
+        filter: FilterDesc::Box { radius: 0.5 },
+        background: BackgroundDesc::Sky(Sky::default()),
+        models: vec![],
+        lights: vec![],
+    };
+    let mut materials = HashMap::new();
+
+    for st in statements(path, text) {
+        let mut params = Params::new(&st)?;
+
+        match st.keyword {
+            "render" => {
+                let s = &mut file.settings;
+                s.width = params.optional("width")?.unwrap_or(s.width);
+                s.height = params.optional("height")?.unwrap_or(s.height);
+                s.samples_per_pixel = params.optional("samples")?
+                    .unwrap_or(s.samples_per_pixel);
+                s.max_depth = params.optional("max_depth")?
+                    .unwrap_or(s.max_depth);
+                s.threads = params.optional("threads")?.or(s.threads);
+                s.seed = params.optional("seed")?.or(s.seed);
+                s.sampler = params.sampler()?.unwrap_or(s.sampler);
+                s.max_samples = params.optional("max_samples")?
+                    .or(s.max_samples);
+                s.error = params.optional_positive("error")?
+                    .map_or(s.error, |error| error as f32);
+                s.pass_samples = params.optional("pass_samples")?
+                    .or(s.pass_samples);
+                s.time_limit = params.optional_positive("time_limit")?
+                    .or(s.time_limit);
+
+                if s.width == 0 || s.height == 0 || s.samples_per_pixel == 0 {
+                    return Err(st.error(
+                        "width, height and samples must be positive".into()
+                    ));
+                }
+                if s.max_depth < 0 {
+                    return Err(st.error(
+                        "max_depth must not be negative".into()
+                    ));
+                }
+                if s.max_samples == Some(0) {
+                    return Err(st.error("max_samples must be positive".into()));
+                }
+                if s.pass_samples == Some(0) {
+                    return Err(st.error(
+                        "pass_samples must be positive".into()
+                    ));
+                }
+            }
+            "camera" => {
+                file.camera = match params.string("type")? {
+                    "orthographic" => CameraDesc::Orthographic {
+                        view: params.optional_look_at()?,
+                        view_width: params.positive("view_width")?,
+                        view_height: params.optional_positive("view_height")?,
+                    },
+                    "perspective" => CameraDesc::Perspective {
+                        view: params.look_at()?,
+                        vertical_fov: params.angle("fov")?,
+                        aspect_ratio: params.optional_positive("aspect")?,
+                    },
+                    "thin_lens" => {
+                        let view = params.look_at()?;
+                        CameraDesc::ThinLens {
+                            view,
+                            vertical_fov: params.angle("fov")?,
+                            aspect_ratio: params.optional_positive("aspect")?,
+                            lens: params.thin_lens(&view)?,
+                        }
+                    }
+                    "equirectangular" => CameraDesc::Equirectangular {
+                        view: params.optional_look_at()?,
+                    },
+                    "fisheye" => CameraDesc::Fisheye {
+                        view: params.optional_look_at()?,
+                        fov: params.fisheye_fov()?,
+                        projection: params.projection()?,
+                    },
+                    "cubemap" => CameraDesc::Cubemap {
+                        view: params.optional_look_at()?,
+                    },
+                    "stereo" => {
+                        let kind = params.string("projection")?;
+                        let (view, projection) = match kind {
+                            "perspective" => (
+                                params.look_at()?,
+                                StereoProjection::Perspective {
+                                    vertical_fov: params.angle("fov")?,
+                                    aspect_ratio: params.optional_positive(
+                                        "aspect"
+                                    )?,
+                                },
+                            ),
+                            "equirectangular" => (
+                                params.optional_look_at()?,
+                                StereoProjection::Equirectangular,
+                            ),
+                            other => return Err(st.error(format!(
+                                "unknown stereo projection '{}'", other
+                            ))),
+                        };
+
+                        CameraDesc::Stereo {
+                            view,
+                            projection,
+                            stereo: params.stereo()?,
+                        }
+                    }
+                    other => return Err(st.error(format!(
+                        "unknown camera type '{}'", other
+                    ))),
+                };
+            }
+            "filter" => {
+                file.filter = params.filter()?;
+            }
+            "sky" => {
+                let sky = match file.background {
+                    BackgroundDesc::Sky(sky) => sky,
+                    _ => Sky::default(),
+                };
+                file.background = BackgroundDesc::Sky(params.sky(sky)?);
+            }
+            "background" => {
+                file.background = match params.string("type")? {
+                    "sky" => BackgroundDesc::Sky(params.sky(Sky::default())?),
+                    "constant" => {
+                        BackgroundDesc::Constant(params.color("color")?)
+                    }
+                    "environment" => {
+                        let dir = path.parent()
+                            .unwrap_or_else(|| Path::new(""));
+                        let image_path = dir.join(params.string("file")?);
+                        let image = read_image(&image_path).map_err(|err| {
+                            ParseError::io(&image_path, err)
+                        })?;
+                        BackgroundDesc::Environment {
+                            image,
+                            rotation: params.optional("rotation")?
+                                .unwrap_or(0.0),
+                            intensity: params.optional_positive("intensity")?
+                                .unwrap_or(1.0) as f32,
+                        }
+                    }
+                    other => return Err(st.error(format!(
+                        "unknown background type '{}'", other
+                    ))),
+                };
+            }
+            "material" => {
+                let name = params.string("name")?.to_string();
+                let desc = match params.string("type")? {
+                    "lambertian" => MaterialDesc::Lambertian {
+                        albedo: params.vector("albedo")?,
+                    },
+                    "metal" => MaterialDesc::Metal {
+                        albedo: params.vector("albedo")?,
+                        fuzz: params.optional("fuzz")?.unwrap_or(0.0),
+                    },
+                    "dielectric" => MaterialDesc::Dielectric {
+                        refractive_index: params.positive("index")?,
+                    },
+                    "diffuse_light" => MaterialDesc::DiffuseLight {
+                        emit: params.color("emit")?,
+                        two_sided: params.optional("two_sided")?
+                            .unwrap_or(false),
+                    },
+                    other => return Err(st.error(format!(
+                        "unknown material type '{}'", other
+                    ))),
+                };
+                if materials.insert(name.clone(), desc).is_some() {
+                    return Err(st.error(format!(
+                        "material '{}' is defined twice", name
+                    )));
+                }
+            }
+            "sphere" => {
+                file.models.push(Box::new(Sphere::new(
+                    params.point("center")?,
+                    params.positive("radius")?,
+                    params.material(&materials)?,
+                )));
+            }
+            "plane" => {
+                file.models.push(Box::new(Plane::new(
+                    params.point("point")?,
+                    params.nonzero_vector("normal")?,
+                    params.material(&materials)?,
+                )));
+            }
+            "triangle" => {
+                let vertices = [
+                    params.point("a")?,
+                    params.point("b")?,
+                    params.point("c")?,
+                ];
+                file.models.push(Box::new(Triangle::new(
+                    vertices,
+                    params.material(&materials)?,
+                )));
+            }
+            "mesh" => {
+                let dir = path.parent().unwrap_or_else(|| Path::new(""));
+                for mesh in load_obj(dir.join(params.string("file")?))? {
+                    file.models.push(Box::new(mesh));
+                }
+            }
+            "light" => {
+                file.lights.push(params.light()?);
+            }
+            other => {
+                return Err(st.error(format!("unknown statement '{}'", other)));
+            }
+        }
+
+        params.finish()?;
+    }
+
+    Ok(file)
+}
+
+// Looking down -Z from the origin.
+fn default_view() -> LookAt {
+    LookAt::new(
+        Point::origin(),
+        Point::new(0.0, 0.0, -1.0),
+        Vector::new(0.0, 1.0, 0.0),
+    )
+}
+
+// The key=value parameters of a statement. Each getter consumes its key, so
+// that finish() can reject any left over as misspelt or misplaced.
+struct Params<'a> {
+    st: &'a Statement<'a>,
+    values: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Params<'a> {
+    fn new(st: &'a Statement<'a>) -> Result<Self, ParseError> {
+        let mut values = HashMap::new();
+
+        for arg in &st.args {
+            let mut split = arg.splitn(2, '=');
+            let key = split.next().unwrap_or("");
+            let value = match split.next() {
+                Some(value) if !key.is_empty() && !value.is_empty() => value,
+                _ => return Err(st.error(format!(
+                    "expected key=value, found '{}'", arg
+                ))),
+            };
+            if values.insert(key, value).is_some() {
+                return Err(st.error(format!("'{}' given twice", key)));
+            }
+        }
+
+        Ok(Self { st, values })
+    }
+
+    fn string(&mut self, key: &str) -> Result<&'a str, ParseError> {
+        let keyword = self.st.keyword;
+
+        self.values.remove(key).ok_or_else(|| {
+            self.st.error(format!("'{}' needs '{}'", keyword, key))
+        })
+    }
+
+    fn parse<T: FromStr>(&self, key: &str, value: &str)
+        -> Result<T, ParseError>
+    {
+        value.parse().map_err(|_| {
+            self.st.error(format!("invalid {} '{}'", key, value))
+        })
+    }
+
+    fn optional<T: FromStr>(&mut self, key: &str)
+        -> Result<Option<T>, ParseError>
+    {
+        match self.values.remove(key) {
+            Some(value) => self.parse(key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn positive(&mut self, key: &str) -> Result<f64, ParseError> {
+        let value = self.string(key)?;
+        let x: f64 = self.parse(key, value)?;
+
+        if x > 0.0 {
+            Ok(x)
+        } else {
+            Err(self.st.error(format!("{} must be positive", key)))
+        }
+    }
+
+    fn optional_positive(&mut self, key: &str)
+        -> Result<Option<f64>, ParseError>
+    {
+        if self.values.contains_key(key) {
+            self.positive(key).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // A field of view in degrees, strictly between 0 and 180.
+    fn angle(&mut self, key: &str) -> Result<f64, ParseError> {
+        let degrees = self.positive(key)?;
+
+        if degrees < 180.0 {
+            Ok(degrees)
+        } else {
+            Err(self.st.error(format!("{} must be less than 180", key)))
+        }
+    }
+
+    fn triple(&mut self, key: &str) -> Result<(f64, f64, f64), ParseError> {
+        let value = self.string(key)?;
+        let parts = value.split(',')
+            .map(|part| self.parse(key, part))
+            .collect::<Result<Vec<f64>, _>>()?;
+
+        match parts[..] {
+            [x, y, z] => Ok((x, y, z)),
+            _ => Err(self.st.error(format!(
+                "{} needs three comma-separated numbers, found '{}'",
+                key, value
+            ))),
+        }
+    }
+
+    fn point(&mut self, key: &str) -> Result<Point, ParseError> {
+        let (x, y, z) = self.triple(key)?;
+
+        Ok(Point::new(x, y, z))
+    }
+
+    fn vector(&mut self, key: &str) -> Result<Vector, ParseError> {
+        let (x, y, z) = self.triple(key)?;
+
+        Ok(Vector::new(x, y, z))
+    }
+
+    fn nonzero_vector(&mut self, key: &str) -> Result<Vector, ParseError> {
+        let v = self.vector(key)?;
+
+        if v.norm_sqr() > 0.0 {
+            Ok(v)
+        } else {
+            Err(self.st.error(format!("{} must not be zero", key)))
+        }
+    }
+
+    // The eye, target and (optional) up parameters of a camera.
+    fn look_at(&mut self) -> Result<LookAt, ParseError> {
+        let eye = self.point("eye")?;
+        let target = self.point("target")?;
+        let up = if self.values.contains_key("up") {
+            self.nonzero_vector("up")?
+        } else {
+            Vector::new(0.0, 1.0, 0.0)
+        };
+
+        let forward = target - eye;
+        if forward.norm_sqr() == 0.0 {
+            return Err(self.st.error("eye and target must differ".into()));
+        }
+        if forward.cross(up).norm_sqr() < 1e-12 * up.norm_sqr() {
+            return Err(self.st.error(
+                "up must not point along the view direction".into()
+            ));
+        }
+
+        Ok(LookAt::new(eye, target, up))
+    }
+
+    // Like look_at(), but looking down -Z from the origin if given none of
+    // the parameters.
+    fn optional_look_at(&mut self) -> Result<LookAt, ParseError> {
+        let keys = ["eye", "target", "up"];
+
+        if keys.iter().any(|key| self.values.contains_key(key)) {
+            self.look_at()
+        } else {
+            Ok(default_view())
+        }
+    }
+
+    // Fisheyes can see all the way round, to 360 degrees.
+    fn fisheye_fov(&mut self) -> Result<f64, ParseError> {
+        let degrees = self.positive("fov")?;
+
+        if degrees <= 360.0 {
+            Ok(degrees)
+        } else {
+            Err(self.st.error("fov must be at most 360".into()))
+        }
+    }
+
+    fn projection(&mut self) -> Result<FisheyeProjection, ParseError> {
+        match self.values.remove("projection") {
+            None | Some("equidistant") => Ok(FisheyeProjection::Equidistant),
+            Some("equisolid") => Ok(FisheyeProjection::Equisolid),
+            Some(other) => Err(self.st.error(format!(
+                "unknown projection '{}'", other
+            ))),
+        }
+    }
+
+    // Parsed by SamplerDesc, which the command line uses too, so that both
+    // take the same names and report the same errors.
+    fn sampler(&mut self) -> Result<Option<SamplerDesc>, ParseError> {
+        match self.values.remove("sampler") {
+            Some(name) => {
+                name.parse().map(Some).map_err(|err| self.st.error(err))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn stereo(&mut self) -> Result<Stereo, ParseError> {
+        let ipd = self.positive("ipd")?;
+        let convergence = self.optional_positive("convergence")?;
+        let layout = match self.values.remove("layout") {
+            None | Some("side_by_side") => StereoLayout::SideBySide,
+            Some("over_under") => StereoLayout::OverUnder,
+            Some(other) => return Err(self.st.error(format!(
+                "unknown stereo layout '{}'", other
+            ))),
+        };
+
+        Ok(Stereo { ipd, convergence, layout })
+    }
+
+    // The lens of a thin lens camera. It focuses on the target unless told
+    // otherwise, and has a round aperture unless given a number of blades.
+    fn thin_lens(&mut self, view: &LookAt) -> Result<ThinLens, ParseError> {
+        let focal_length = self.positive("focal_length")?;
+        let f_stop = self.positive("f_stop")?;
+        let focus_distance = self.optional_positive("focus")?
+            .unwrap_or_else(|| (view.target - view.eye).norm());
+        let rotation = self.optional("blade_rotation")?;
+
+        let aperture = match self.optional("blades")? {
+            None | Some(0) if rotation.is_none() => Aperture::Circle,
+            Some(blades) if blades >= 3 => Aperture::Polygon {
+                blades,
+                rotation: rotation.unwrap_or(0.0),
+            },
+            _ => return Err(self.st.error(
+                "an aperture needs at least 3 blades".into()
+            )),
+        };
+
+        Ok(ThinLens { focal_length, f_stop, focus_distance, aperture })
+    }
+
+    // Each filter has its own default radius, wide enough for its shape.
+    fn filter(&mut self) -> Result<FilterDesc, ParseError> {
+        let kind = self.string("type")?;
+        let mut radius = |default| {
+            self.optional_positive("radius").map(|r| r.unwrap_or(default))
+        };
+
+        let desc = match kind {
+            "box" => FilterDesc::Box { radius: radius(0.5)? },
+            "tent" => FilterDesc::Tent { radius: radius(1.0)? },
+            "gaussian" => FilterDesc::Gaussian {
+                radius: radius(1.5)?,
+                sigma: self.optional_positive("sigma")?.unwrap_or(0.5),
+            },
+            "mitchell" => FilterDesc::Mitchell {
+                radius: radius(2.0)?,
+                b: self.optional("b")?.unwrap_or(1.0 / 3.0),
+                c: self.optional("c")?.unwrap_or(1.0 / 3.0),
+            },
+            "lanczos" => FilterDesc::Lanczos { radius: radius(3.0)? },
+            other => return Err(self.st.error(format!(
+                "unknown filter type '{}'", other
+            ))),
+        };
+
+        Ok(desc)
+    }
+
+    fn color(&mut self, key: &str) -> Result<Color, ParseError> {
+        let (r, g, b) = self.triple(key)?;
+
+        Ok(Color::new(r as f32, g as f32, b as f32))
+    }
+
+    fn optional_color(&mut self, key: &str)
+        -> Result<Option<Color>, ParseError>
+    {
+        if !self.values.contains_key(key) {
+            return Ok(None);
+        }
+
+        self.color(key).map(Some)
+    }
+
+    // The sky, with any colours given changed.
+    fn sky(&mut self, sky: Sky) -> Result<Sky, ParseError> {
+        Ok(Sky {
+            horizon: self.optional_color("horizon")?.unwrap_or(sky.horizon),
+            zenith: self.optional_color("zenith")?.unwrap_or(sky.zenith),
+        })
+    }
+
+    fn light(&mut self) -> Result<Box<dyn Light>, ParseError> {
+        let light: Box<dyn Light> = match self.string("type")? {
+            "point" => Box::new(PointLight::new(
+                self.point("position")?,
+                self.color("intensity")?,
+            )),
+            "spot" => {
+                let position = self.point("position")?;
+                let target = self.point("target")?;
+                if target == position {
+                    return Err(self.st.error(
+                        "target must not be the light's position".into()
+                    ));
+                }
+                let intensity = self.color("intensity")?;
+                let outer = self.angle("outer")?;
+                let inner = match self.optional_positive("inner")? {
+                    Some(inner) if inner > outer => {
+                        return Err(self.st.error(
+                            "inner must not be wider than outer".into()
+                        ));
+                    }
+                    Some(inner) => inner,
+                    None => outer,
+                };
+
+                Box::new(SpotLight::new(
+                    position, target, intensity, inner, outer,
+                ))
+            }
+            "directional" => Box::new(DirectionalLight::new(
+                self.nonzero_vector("direction")?,
+                self.color("irradiance")?,
+            )),
+            other => return Err(self.st.error(format!(
+                "unknown light type '{}'", other
+            ))),
+        };
+
+        Ok(light)
+    }
+
+    fn material(&mut self, materials: &HashMap<String, MaterialDesc>)
+        -> Result<Box<dyn Material>, ParseError>
+    {
+        let name = self.string("material")?;
+
+        match materials.get(name) {
+            Some(desc) => Ok(desc.build()),
+            None => Err(self.st.error(format!(
+                "unknown material '{}'", name
+            ))),
+        }
+    }
+
+    fn finish(self) -> Result<(), ParseError> {
+        let mut keys: Vec<_> = self.values.keys().collect();
+        keys.sort();
+
+        match keys.first() {
+            Some(key) => Err(self.st.error(format!(
+                "'{}' takes no parameter '{}'", self.st.keyword, key
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::ImageFormat;
+
+    use super::*;
+
+    const SPHERES: &str = "\
+# Two spheres.
+render width=20 height=10 samples=4 max_depth=8 threads=2 sampler=halton
+render max_samples=64 error=0.05 pass_samples=2 time_limit=1.5
+camera type=orthographic view_width=4
+sky horizon=1,1,1 zenith=0,0,1
+
+material name=red type=lambertian albedo=0.8,0.3,0.3
+material name=gold type=metal albedo=0.8,0.6,0.2 fuzz=0.3
+material name=glass type=dielectric index=1.5
+material name=lamp type=diffuse_light emit=4,4,4 two_sided=true
+
+sphere center=0,0,-1 radius=0.5 material=red
+sphere center=0,3,-1 radius=1 material=lamp
+sphere center=1,0,-1 radius=0.5 material=gold   # Shiny.
+plane point=0,-0.5,0 normal=0,1,0 material=glass
+triangle a=0,0,0 b=1,0,0 c=0,1,0 material=red
+
+light type=point position=0,5,0 intensity=10,10,10
+light type=spot position=0,5,0 target=0,0,-1 intensity=20,20,20 outer=30
+light type=directional direction=-1,-1,-1 irradiance=1,1,0.9
+";
+
+    #[test]
+    fn parse() {
+        let file = parse_scene(Path::new("spheres.scene"), SPHERES).unwrap();
+
+        assert_eq!(file.settings, RenderSettings {
+            width: 20,
+            height: 10,
+            samples_per_pixel: 4,
+            max_depth: 8,
+            threads: Some(2),
+            seed: None,
+            sampler: SamplerDesc::Halton,
+            max_samples: Some(64),
+            error: 0.05,
+            pass_samples: Some(2),
+            time_limit: Some(1.5),
+        });
+        assert_eq!(file.settings.progressive(), Some(Progressive {
+            pass_samples: 2,
+            time_limit: Some(Duration::from_millis(1500)),
+        }));
+        assert_eq!(file.camera, CameraDesc::Orthographic {
+            view: default_view(),
+            view_width: 4.0,
+            view_height: None,
+        });
+        assert_eq!(file.background, BackgroundDesc::Sky(Sky {
+            horizon: Color::new(1.0, 1.0, 1.0),
+            zenith: Color::new(0.0, 0.0, 1.0),
+        }));
+        assert_eq!(file.models.len(), 5);
+        assert_eq!(file.lights.len(), 3);
+
+        let camera = file.camera.build(&file.settings);
+        assert_eq!(camera.get_image_width(), 20);
+        assert_eq!(camera.get_samples_per_pixel(), 4);
+    }
+
+    #[test]
+    fn perspective_camera() {
+        let text = "camera type=perspective eye=0,1,2 target=0,0,-1 fov=40\n";
+        let file = parse_scene(Path::new("p.scene"), text).unwrap();
+
+        assert_eq!(file.camera, CameraDesc::Perspective {
+            view: LookAt::new(
+                Point::new(0.0, 1.0, 2.0),
+                Point::new(0.0, 0.0, -1.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            vertical_fov: 40.0,
+            aspect_ratio: None,
+        });
+    }
+
+    #[test]
+    fn orthographic_camera() {
+        let text = "\
+camera type=orthographic eye=5,5,5 target=0,0,0 view_width=6 view_height=4
+";
+        let file = parse_scene(Path::new("o.scene"), text).unwrap();
+
+        assert_eq!(file.camera, CameraDesc::Orthographic {
+            view: LookAt::new(
+                Point::new(5.0, 5.0, 5.0),
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            view_width: 6.0,
+            view_height: Some(4.0),
+        });
+    }
+
+    #[test]
+    fn panoramic_cameras() {
+        let camera = |text: &str| {
+            parse_scene(Path::new("p.scene"), text).unwrap().camera
+        };
+
+        assert_eq!(
+            camera("camera type=equirectangular eye=0,1,0 target=0,1,-1\n"),
+            CameraDesc::Equirectangular {
+                view: LookAt::new(
+                    Point::new(0.0, 1.0, 0.0),
+                    Point::new(0.0, 1.0, -1.0),
+                    Vector::new(0.0, 1.0, 0.0),
+                ),
+            },
+        );
+        assert_eq!(
+            camera("camera type=fisheye fov=220 projection=equisolid\n"),
+            CameraDesc::Fisheye {
+                view: default_view(),
+                fov: 220.0,
+                projection: FisheyeProjection::Equisolid,
+            },
+        );
+
+        let file = parse_scene(
+            Path::new("c.scene"),
+            "render width=192 height=32\ncamera type=cubemap\n",
+        ).unwrap();
+        let cubemap = file.camera.build(&file.settings);
+        assert_eq!(cubemap.get_image_width(), 6 * 32);
+        assert_eq!(cubemap.get_image_height(), 32);
+    }
+
+    #[test]
+    fn stereo_camera() {
+        let text = "\
+render width=64 height=48
+camera type=stereo projection=equirectangular ipd=0.064 layout=over_under
+";
+        let file = parse_scene(Path::new("s.scene"), text).unwrap();
+
+        assert_eq!(file.camera, CameraDesc::Stereo {
+            view: default_view(),
+            projection: StereoProjection::Equirectangular,
+            stereo: Stereo {
+                ipd: 0.064,
+                convergence: None,
+                layout: StereoLayout::OverUnder,
+            },
+        });
+        let camera = file.camera.build(&file.settings);
+        assert_eq!(camera.get_image_width(), 64);
+        assert_eq!(camera.get_image_height(), 48);
+    }
+
+    #[test]
+    fn image_sizes() {
+        let check = |text: &str| {
+            let file = parse_scene(Path::new("i.scene"), text).unwrap();
+            file.camera.check_image_size(&file.settings)
+        };
+
+        assert_eq!(check("render width=60 height=10\ncamera type=cubemap\n"),
+                   Ok(()));
+        assert_eq!(
+            check("render width=64 height=4\ncamera type=cubemap\n"),
+            Err("a cubemap camera needs an image 6 times as wide as it is \
+                 tall, not 64x4".into()),
+        );
+        assert_eq!(
+            check("render width=1\ncamera type=stereo \
+                   projection=equirectangular ipd=0.064\n"),
+            Err("a stereo camera needs an even image width of at least 2 to \
+                 split between the eyes, not 1".into()),
+        );
+        assert_eq!(
+            check("render height=5\ncamera type=stereo \
+                   projection=equirectangular ipd=0.064 layout=over_under\n"),
+            Err("a stereo camera needs an even image height of at least 2 to \
+                 split between the eyes, not 5".into()),
+        );
+    }
+
+    #[test]
+    fn thin_lens_camera() {
+        let text = "\
+camera type=thin_lens eye=0,0,3 target=0,0,-1 fov=30 focal_length=0.1 \
+       f_stop=2 blades=5
+";
+        let file = parse_scene(Path::new("t.scene"), text);
+        let lens = match file.unwrap().camera {
+            CameraDesc::ThinLens { lens, .. } => lens,
+            other => panic!("wrong camera {:?}", other),
+        };
+
+        assert_eq!(lens, ThinLens {
+            focal_length: 0.1,
+            f_stop: 2.0,
+            focus_distance: 4.0,
+            aperture: Aperture::Polygon { blades: 5, rotation: 0.0 },
+        });
+    }
+
+    #[test]
+    fn filters() {
+        let filter = |text| {
+            parse_scene(Path::new("filter.scene"), text).unwrap().filter
+        };
+
+        assert_eq!(filter(""), FilterDesc::Box { radius: 0.5 });
+        assert_eq!(
+            filter("filter type=tent radius=1.5\n"),
+            FilterDesc::Tent { radius: 1.5 },
+        );
+        assert_eq!(
+            filter("filter type=gaussian sigma=0.7\n"),
+            FilterDesc::Gaussian { radius: 1.5, sigma: 0.7 },
+        );
+        assert_eq!(
+            filter("filter type=mitchell b=0 c=0.5\n"),
+            FilterDesc::Mitchell { radius: 2.0, b: 0.0, c: 0.5 },
+        );
+        assert_eq!(
+            filter("filter type=lanczos\n"),
+            FilterDesc::Lanczos { radius: 3.0 },
+        );
+    }
+
+    #[test]
+    fn backgrounds() {
+        let dir = std::env::temp_dir().join("rt-scene-backgrounds");
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = Image::new(2, 1, vec![
+            Color::new(1.0, 2.0, 3.0),
+            Color::new(4.0, 5.0, 6.0),
+        ]);
+        let mut out = std::fs::File::create(dir.join("sky.pfm")).unwrap();
+        ImageFormat::Pfm.writer().write(&image, &mut out).unwrap();
+
+        let background = |text| {
+            parse_scene(&dir.join("b.scene"), text).unwrap().background
+        };
+
+        assert_eq!(background(""), BackgroundDesc::Sky(Sky::default()));
+        assert_eq!(
+            background("background type=constant color=0,0,0\n"),
+            BackgroundDesc::Constant(Color::black()),
+        );
+        assert_eq!(
+            background("background type=constant color=1,1,1\n\
+                        sky zenith=0,0,1\n"),
+            BackgroundDesc::Sky(Sky {
+                zenith: Color::new(0.0, 0.0, 1.0),
+                ..Sky::default()
+            }),
+        );
+        assert_eq!(
+            background("background type=environment file=sky.pfm \
+                        rotation=90 intensity=2\n"),
+            BackgroundDesc::Environment {
+                image,
+                rotation: 90.0,
+                intensity: 2.0,
+            },
+        );
+    }
+
+    #[test]
+    fn example_scenes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension() == Some("scene".as_ref()) {
+                if let Err(err) = load_scene(&path) {
+                    panic!("{}", err);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let err = |text| {
+            parse_scene(Path::new("bad.scene"), text).err().unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            err("# Fine.\nspehre center=0,0,0\n"),
+            "bad.scene:2: unknown statement 'spehre'",
+        );
+        assert_eq!(
+            err("sphere center=0,0,0 radius=1 material=red\n"),
+            "bad.scene:1: unknown material 'red'",
+        );
+        assert_eq!(
+            err("material name=m type=lambertian albedo=1,1\n"),
+            "bad.scene:1: albedo needs three comma-separated numbers, \
+             found '1,1'",
+        );
+        assert_eq!(
+            err("render width=10 hieght=10\n"),
+            "bad.scene:1: 'render' takes no parameter 'hieght'",
+        );
+        assert_eq!(
+            err("\n\ncamera type=orthographic\n"),
+            "bad.scene:3: 'camera' needs 'view_width'",
+        );
+        assert_eq!(
+            err("camera type=perspective eye=0,0,0 target=0,1,0 fov=30\n"),
+            "bad.scene:1: up must not point along the view direction",
+        );
+        assert_eq!(
+            err("camera type=perspective eye=0,0,0 target=0,0,1 fov=180\n"),
+            "bad.scene:1: fov must be less than 180",
+        );
+        assert_eq!(
+            err("camera type=thin_lens eye=0,0,0 target=0,0,1 fov=30 \
+                 focal_length=1 f_stop=8 blades=2\n"),
+            "bad.scene:1: an aperture needs at least 3 blades",
+        );
+        assert_eq!(
+            err("render max_samples=0\n"),
+            "bad.scene:1: max_samples must be positive",
+        );
+        assert_eq!(
+            err("render samples=4\nrender max_depth=-5\n"),
+            "bad.scene:2: max_depth must not be negative",
+        );
+        assert_eq!(
+            err("render sampler=sobel\n"),
+            "bad.scene:1: unknown sampler 'sobel' (expected independent, \
+             stratified, halton or sobol)",
+        );
+        assert_eq!(
+            err("filter type=sinc\n"),
+            "bad.scene:1: unknown filter type 'sinc'",
+        );
+        assert_eq!(
+            err("filter type=box radius=0\n"),
+            "bad.scene:1: radius must be positive",
+        );
+        assert_eq!(
+            err("material name=m type=diffuse_light emit=1,1,1 two_sided=1\n"),
+            "bad.scene:1: invalid two_sided '1'",
+        );
+        assert_eq!(
+            err("light type=spot position=0,1,0 target=0,0,0 intensity=1,1,1 \
+                 inner=40 outer=30\n"),
+            "bad.scene:1: inner must not be wider than outer",
+        );
+        assert_eq!(
+            err("light type=area\n"),
+            "bad.scene:1: unknown light type 'area'",
+        );
+        assert_eq!(
+            err("background type=starfield\n"),
+            "bad.scene:1: unknown background type 'starfield'",
+        );
+        assert!(
+            err("background type=environment file=missing.hdr\n")
+                .starts_with("missing.hdr: "),
+        );
+        assert_eq!(
+            err("render width=ten\n"),
+            "bad.scene:1: invalid width 'ten'",
+        );
+        assert_eq!(
+            err("sky horizon\n"),
+            "bad.scene:1: expected key=value, found 'horizon'",
+        );
+    }
+}
@@ -2,52 +2,126 @@
 // Written by quadfault
 // 10/18/18
 
-mod cameras;
-mod materials;
-mod math;
-mod models;
-mod scene;
-
-use self::cameras::OrthographicCamera;
-use self::math::{ Point, Vector };
-use self::materials::*;
-use self::models::*;
-use self::scene::Scene;
+mod cli;
+
+use std::env;
+use std::fs::File;
+use std::io::{ self, BufWriter };
+use std::path::Path;
+use std::process;
+use std::time::{ Duration, Instant };
+
+use rt::Progress;
+use rt::film::Film;
+use rt::image::{ Image, ImageFormat };
+use rt::loaders::load_scene;
+
+use self::cli::{ Command, USAGE };
 
 fn main() {
-    let scene = build_scene();
-    scene.render();
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Render(options)) => options,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("rt: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut file = match load_scene(&options.scene) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("rt: {}", err);
+            process::exit(1);
+        }
+    };
+    if let Err(err) = options.configure(&mut file) {
+        eprintln!("rt: {}", err);
+        process::exit(2);
+    }
+    let settings = file.settings.clone();
+    let scene = file.build();
+
+    let mut film = scene.film();
+    match settings.progressive() {
+        // Snapshots only make sense in a file, and the last pass's image is
+        // written below like any other.
+        Some(progressive) => {
+            let every = options.snapshot_every.map(Duration::from_secs_f64);
+            let mut last_snapshot: Option<Duration> = None;
+            let mut snapshot = |film: &Film, progress: &Progress| {
+                let due = match (every, last_snapshot) {
+                    (Some(every), Some(last)) => {
+                        progress.elapsed - last >= every
+                    }
+                    _ => true,
+                };
+                let last = progress.samples_per_pixel
+                    >= settings.samples_per_pixel;
+
+                if let Some(ref path) = options.output {
+                    if due && !last {
+                        write_image_or_exit(&film.image(), path);
+                        last_snapshot = Some(progress.elapsed);
+                    }
+                }
+            };
+            scene.render_progressive(&mut film, &progressive, &mut snapshot);
+        }
+        // Without passes, snapshots are taken as tiles finish instead.
+        None => match (options.snapshot_every, &options.output) {
+            (Some(every), Some(path)) => {
+                let every = Duration::from_secs_f64(every);
+                let mut last_snapshot = Instant::now();
+                scene.render_to_with(&mut film, |film| {
+                    if last_snapshot.elapsed() >= every {
+                        write_image_or_exit(&film.image(), path);
+                        last_snapshot = Instant::now();
+                    }
+                });
+            }
+            _ => scene.render_to(&mut film),
+        },
+    }
+
+    let image = film.image();
+    let result = match options.output {
+        Some(ref path) => write_image(&image, path),
+        None => {
+            let stdout = io::stdout();
+            let mut out = BufWriter::new(stdout.lock());
+            ImageFormat::Ppm.writer().write(&image, &mut out)
+        }
+    };
+    if let Err(err) = result {
+        let target = match options.output {
+            Some(ref path) => path.display().to_string(),
+            None => "standard output".to_string(),
+        };
+        eprintln!("rt: can't write to {}: {}", target, err);
+        process::exit(1);
+    }
+
+    if let Some(ref path) = options.heatmap {
+        write_image_or_exit(&film.heatmap(), path);
+    }
+}
+
+// The format was checked when the arguments were parsed.
+fn write_image(image: &Image, path: &Path) -> io::Result<()> {
+    let writer = ImageFormat::from_path(path).unwrap().writer();
+
+    File::create(path).and_then(|f| {
+        writer.write(image, &mut BufWriter::new(f))
+    })
 }
 
-fn build_scene() -> Scene {
-    let mut scene = Scene::new(
-        Box::new(OrthographicCamera::new(1000, 500, 4.0, 1000)),
-    );
-    //scene.add(Box::new(Plane::new(
-    //    Point::new(0.0, 0.0, -3.0),
-    //    Vector::new(0.0, 0.0, 1.0),
-    //    Box::new(Lambertian::new(Vector::new(1.0, 0.0, 1.0))),
-    //)));
-    scene.add(Box::new(Sphere::new(
-        Point::new(0.0, 0.0, -1.0),
-        0.5,
-        Box::new(Lambertian::new(Vector::new(0.8, 0.3, 0.3)))
-    )));
-    scene.add(Box::new(Sphere::new(
-        Point::new(0.0, -100.5, -1.0),
-        100.0,
-        Box::new(Lambertian::new(Vector::new(0.8, 0.8, 0.0)))
-    )));
-    scene.add(Box::new(Sphere::new(
-        Point::new(1.0, 0.0, -1.0),
-        0.5,
-        Box::new(Metal::new(Vector::new(0.8, 0.6, 0.2), 0.3))
-    )));
-    scene.add(Box::new(Sphere::new(
-        Point::new(-1.0, 0.0, -1.0),
-        0.5,
-        Box::new(Metal::new(Vector::new(0.8, 0.8, 0.8), 0.0))
-    )));
-
-    scene
+fn write_image_or_exit(image: &Image, path: &Path) {
+    if let Err(err) = write_image(image, path) {
+        eprintln!("rt: can't write to {}: {}", path.display(), err);
+        process::exit(1);
+    }
 }
@@ -2,12 +2,11 @@
 // Written by quadfault
 // 10/24/18
 
-use rand::prelude::*;
-
 use crate::models::HitResult;
-use crate::math::{ Ray, Vector };
+use crate::math::Vector;
+use crate::samplers::Sampler;
 
-use super::{ Material, ScatterResult, reflect, refract, schlick };
+use super::{ BsdfSample, Material, reflect, refract, schlick };
 
 pub struct Dielectric {
     refractive_index: f64,
@@ -19,30 +18,37 @@ impl Dielectric {
     }
 }
 
+// Reflects or refracts, picking one or the other by the Fresnel
+// reflectance, so both lobes are delta lobes.
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hr: &HitResult) -> Option<ScatterResult> {
-        let reflected = reflect(ray.direction, hr.normal);
+    fn eval(&self, _hr: &HitResult, _wo: Vector, _wi: Vector) -> Vector {
+        Vector::zero()
+    }
+
+    fn sample(&self, hr: &HitResult, wo: Vector, sampler: &mut dyn Sampler)
+        -> Option<BsdfSample>
+    {
+        let direction = -wo;
+        let reflected = reflect(direction, hr.normal);
         let outward_normal;
         let ni_over_nt;
         let cosine;
 
-        if ray.direction.dot(hr.normal) > 0.0 {
+        if direction.dot(hr.normal) > 0.0 {
             outward_normal = -hr.normal;
             ni_over_nt = self.refractive_index;
-            cosine = self.refractive_index
-                * ray.direction.dot(hr.normal)
-                / ray.direction.norm();
+            cosine = self.refractive_index * direction.dot(hr.normal);
         } else {
             outward_normal = hr.normal;
             ni_over_nt = 1.0 / self.refractive_index;
-            cosine = -ray.direction.dot(hr.normal) / ray.direction.norm();
+            cosine = -direction.dot(hr.normal);
         }
 
         let reflect_prob;
         let mut refracted = Vector::zero();
-        match refract(ray.direction, outward_normal, ni_over_nt) {
+        match refract(direction, outward_normal, ni_over_nt) {
             Some(r) => {
-                refracted = r;
+                refracted = r.hat();
                 reflect_prob = schlick(cosine, self.refractive_index);
             }
             None => {
@@ -50,16 +56,67 @@ impl Material for Dielectric {
             }
         }
 
-        if thread_rng().gen::<f64>() < reflect_prob {
-            Some(ScatterResult {
-                scattered: Ray::new(hr.hit_point, reflected),
-                attenuation: Vector::new(1.0, 1.0, 1.0),
-            })
+        let (wi, pdf) = if sampler.get_1d() < reflect_prob {
+            (reflected, reflect_prob)
         } else {
-            Some(ScatterResult {
-                scattered: Ray::new(hr.hit_point, refracted),
-                attenuation: Vector::new(1.0, 1.0, 1.0),
-            })
+            (refracted, 1.0 - reflect_prob)
+        };
+        let cosine = wi.dot(hr.normal).abs();
+        if cosine == 0.0 {
+            return None;
+        }
+
+        // The Fresnel weight and the chance of picking the lobe cancel out,
+        // so whichever is picked carries all the light.
+        Some(BsdfSample {
+            wi,
+            f: Vector::new(1.0, 1.0, 1.0) * (pdf / cosine),
+            pdf,
+            delta: true,
+        })
+    }
+
+    fn pdf(&self, _hr: &HitResult, _wo: Vector, _wi: Vector) -> f64 {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::tests::hit;
+    use crate::samplers::IndependentSampler;
+
+    use super::*;
+
+    #[test]
+    fn passes_all_light_on() {
+        let glass = Dielectric::new(1.5);
+        let hr = hit(&glass);
+        let mut sampler = IndependentSampler::new();
+        let (mut reflections, mut refractions) = (0, 0);
+
+        // From outside, then from inside at beyond the critical angle.
+        for &wo in &[Vector::new(0.6, 0.0, 0.8), Vector::new(0.8, 0.0, -0.6)] {
+            for i in 0..1000 {
+                sampler.start_pixel_sample(0, 0, i, 1000);
+                let sample = glass.sample(&hr, wo, &mut sampler).unwrap();
+                let weight = sample.f * (sample.wi.z.abs() / sample.pdf);
+
+                assert!(sample.delta);
+                assert!((weight - Vector::new(1.0, 1.0, 1.0)).norm() < 1e-9);
+                assert_eq!(glass.eval(&hr, wo, sample.wi), Vector::zero());
+                assert_eq!(glass.pdf(&hr, wo, sample.wi), 0.0);
+                if sample.wi.z * wo.z > 0.0 {
+                    reflections += 1;
+                } else {
+                    refractions += 1;
+                }
+            }
         }
+
+        // Glass reflects about 4% of the light from outside, and all of it
+        // from inside beyond the critical angle.
+        assert!(refractions > 900 && refractions < 1000);
+        assert_eq!(reflections + refractions, 2000);
     }
 }
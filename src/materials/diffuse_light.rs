@@ -0,0 +1,90 @@
+// materials/diffuse_light.rs - Light-emitting material.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Color, Ray, Vector };
+use crate::models::HitResult;
+use crate::samplers::Sampler;
+
+use super::{ BsdfSample, Material };
+
+// Gives off the same light in every direction from the side the surface
+// faces (or from both sides, if two-sided) and reflects nothing. Turns any
+// model into an area light.
+pub struct DiffuseLight {
+    emit: Color,
+    two_sided: bool,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit, two_sided: false }
+    }
+
+    pub fn two_sided(emit: Color) -> Self {
+        Self { emit, two_sided: true }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn eval(&self, _hr: &HitResult, _wo: Vector, _wi: Vector) -> Vector {
+        Vector::zero()
+    }
+
+    fn sample(&self,
+              _hr: &HitResult,
+              _wo: Vector,
+              _sampler: &mut dyn Sampler)
+        -> Option<BsdfSample>
+    {
+        None
+    }
+
+    fn pdf(&self, _hr: &HitResult, _wo: Vector, _wi: Vector) -> f64 {
+        0.0
+    }
+
+    fn emitted(&self, ray: &Ray, hr: &HitResult) -> Color {
+        if self.two_sided || ray.direction.dot(hr.geometric_normal) < 0.0 {
+            self.emit
+        } else {
+            Color::black()
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.emit != Color::black()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Point;
+    use crate::samplers::IndependentSampler;
+
+    use super::*;
+
+    #[test]
+    fn emits_from_the_front() {
+        let light = DiffuseLight::new(Color::new(4.0, 2.0, 1.0));
+        let up = Vector::new(0.0, 1.0, 0.0);
+        let hr = HitResult {
+            t: 1.0,
+            hit_point: Point::origin(),
+            normal: up,
+            geometric_normal: up,
+            uv: (0.0, 0.0),
+            material: &light,
+        };
+        let from_above = Ray::new(Point::new(0.0, 1.0, 0.0), -up);
+        let from_below = Ray::new(Point::new(0.0, -1.0, 0.0), up);
+        let mut sampler = IndependentSampler::new();
+
+        assert_eq!(light.emitted(&from_above, &hr), Color::new(4.0, 2.0, 1.0));
+        assert_eq!(light.emitted(&from_below, &hr), Color::black());
+        assert!(light.sample(&hr, up, &mut sampler).is_none());
+
+        let both = DiffuseLight::two_sided(Color::new(1.0, 1.0, 1.0));
+        assert_eq!(both.emitted(&from_below, &hr), Color::new(1.0, 1.0, 1.0));
+    }
+}
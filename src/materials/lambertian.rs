@@ -2,11 +2,16 @@
 // Written by quadfault
 // 10/24/18
 
-use crate::math::{ Ray, Vector };
+use std::f64::consts::PI;
+
+use crate::math::Vector;
 use crate::models::HitResult;
+use crate::samplers::Sampler;
 
-use super::{ Material, ScatterResult, random_in_unit_sphere };
+use super::{ BsdfSample, Material, facing, random_unit_vector };
 
+// Scatters light equally in every direction on whichever side of the
+// surface it's seen from.
 pub struct Lambertian {
     albedo: Vector,
 }
@@ -18,12 +23,78 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, hr: &HitResult) -> Option<ScatterResult> {
-        let target = hr.hit_point + hr.normal + random_in_unit_sphere();
+    fn eval(&self, hr: &HitResult, wo: Vector, wi: Vector) -> Vector {
+        if wi.dot(facing(hr.normal, wo)) > 0.0 {
+            self.albedo / PI
+        } else {
+            Vector::zero()
+        }
+    }
+
+    fn sample(&self, hr: &HitResult, wo: Vector, sampler: &mut dyn Sampler)
+        -> Option<BsdfSample>
+    {
+        // Points on the unit sphere resting on the surface lie in
+        // cosine-weighted directions from the hit.
+        let normal = facing(hr.normal, wo);
+        let wi = (normal + random_unit_vector(sampler)).hat();
+        let pdf = wi.dot(normal) / PI;
+
+        if pdf > 0.0 {
+            Some(BsdfSample {
+                wi,
+                f: self.albedo / PI,
+                pdf,
+                delta: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn pdf(&self, hr: &HitResult, wo: Vector, wi: Vector) -> f64 {
+        wi.dot(facing(hr.normal, wo)).max(0.0) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::tests::{ albedo_and_pdf_total, hit };
+    use crate::samplers::IndependentSampler;
+
+    use super::*;
+
+    #[test]
+    fn conserves_energy() {
+        let white = Lambertian::new(Vector::new(1.0, 1.0, 1.0));
+
+        for &wo in &[
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(0.6, 0.0, 0.8),
+            Vector::new(0.0, 0.6, -0.8),
+        ] {
+            let (albedo, pdf_total) = albedo_and_pdf_total(&white, wo);
+            assert!((albedo - 1.0).abs() < 1e-3, "albedo {}", albedo);
+            assert!((pdf_total - 1.0).abs() < 1e-3, "pdf {}", pdf_total);
+        }
+    }
+
+    #[test]
+    fn samples_match_eval_and_pdf() {
+        let grey = Lambertian::new(Vector::new(0.5, 0.5, 0.5));
+        let hr = hit(&grey);
+        let wo = Vector::new(0.0, -0.6, -0.8);
+        let mut sampler = IndependentSampler::new();
+
+        for i in 0..100 {
+            sampler.start_pixel_sample(0, 0, i, 100);
+            let sample = grey.sample(&hr, wo, &mut sampler).unwrap();
 
-        Some(ScatterResult {
-            scattered: Ray::new(hr.hit_point, target - hr.hit_point),
-            attenuation: self.albedo,
-        })
+            // Seen from below, it scatters below.
+            assert!(sample.wi.z < 0.0);
+            assert!(!sample.delta);
+            assert_eq!(sample.f, grey.eval(&hr, wo, sample.wi));
+            assert_eq!(sample.pdf, grey.pdf(&hr, wo, sample.wi));
+        }
     }
 }
@@ -3,44 +3,85 @@
 // 10/20/18
 
 mod dielectric;
+mod diffuse_light;
 mod lambertian;
 mod metal;
 
 pub use self::dielectric::*;
+pub use self::diffuse_light::*;
 pub use self::lambertian::*;
 pub use self::metal::*;
 
-use rand::prelude::*;
+use std::f64::consts::PI;
 
 use crate::models::HitResult;
-use crate::math::{ Ray, Vector };
+use crate::math::{ Color, Ray, Vector };
+use crate::samplers::Sampler;
 
-pub struct ScatterResult {
-    pub scattered: Ray,
-    pub attenuation: Vector,
+// A direction picked by a material's BSDF.
+pub struct BsdfSample {
+    pub wi: Vector,
+    pub f: Vector,
+    // The density by solid angle with which `wi` was picked. A delta lobe,
+    // which only ever scatters one way, gives the chance of picking it
+    // instead, with `f` divided by the same delta as the density so that
+    // f * cos / pdf still comes out right.
+    pub pdf: f64,
+    pub delta: bool,
 }
 
-pub trait Material {
-    fn scatter(&self, ray: &Ray, hr: &HitResult) -> Option<ScatterResult>;
-}
+// Materials scatter light by their BSDF (bidirectional scattering
+// distribution function), f(wo, wi): how much of the light arriving from
+// direction wi leaves towards wo. Both are unit vectors pointing away from
+// the surface, and wo points back along the ray.
+pub trait Material: Send + Sync {
+    // f(wo, wi). Zero for delta lobes, which no other direction can find.
+    fn eval(&self, hr: &HitResult, wo: Vector, wi: Vector) -> Vector;
 
-fn random_in_unit_sphere() -> Vector {
-    let mut rng = thread_rng();
-    let mut p;
-    loop {
-        p = (Vector::new(
-                rng.gen(),
-                rng.gen(),
-                rng.gen()
-            ) * 2.0)
-          - Vector::new(1.0, 1.0, 1.0);
-
-        if p.norm_sqr() < 1.0 {
-            break;
-        }
+    // Picks a direction for the light to have come from, importance
+    // sampling the BSDF. None if it was absorbed.
+    fn sample(&self, hr: &HitResult, wo: Vector, sampler: &mut dyn Sampler)
+        -> Option<BsdfSample>;
+
+    // The density by solid angle with which `sample` picks wi. Zero for
+    // delta lobes.
+    fn pdf(&self, hr: &HitResult, wo: Vector, wi: Vector) -> f64;
+
+    // The light the surface gives off at the hit, back along the ray. Most
+    // materials give off none.
+    fn emitted(&self, _ray: &Ray, _hr: &HitResult) -> Color {
+        Color::black()
     }
 
-    p
+    // Whether the material gives off any light, making models of it worth
+    // sampling as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+}
+
+// The normal turned towards wo, for materials that scatter alike from
+// either side of the surface.
+fn facing(normal: Vector, wo: Vector) -> Vector {
+    if normal.dot(wo) < 0.0 { -normal } else { normal }
+}
+
+// A uniformly distributed unit vector, from two dimensions of the sampler.
+fn random_unit_vector(sampler: &mut dyn Sampler) -> Vector {
+    let (a, b) = sampler.get_2d();
+    let z = 1.0 - 2.0 * a;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * b;
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+// A uniformly distributed point in the unit ball, from three dimensions of
+// the sampler: two for the direction and one for the distance.
+fn random_in_unit_sphere(sampler: &mut dyn Sampler) -> Vector {
+    let direction = random_unit_vector(sampler);
+
+    direction * sampler.get_1d().cbrt()
 }
 
 fn reflect(v: Vector, n: Vector) -> Vector {
@@ -64,3 +105,58 @@ fn schlick(cosine: f64, refractive_index: f64) -> f64 {
 
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Point;
+
+    use super::*;
+
+    // A hit at the origin on a surface facing +z.
+    pub fn hit(material: &dyn Material) -> HitResult<'_> {
+        let up = Vector::new(0.0, 0.0, 1.0);
+
+        HitResult {
+            t: 1.0,
+            hit_point: Point::origin(),
+            normal: up,
+            geometric_normal: up,
+            uv: (0.0, 0.0),
+            material,
+        }
+    }
+
+    // The integral of `f` over every direction, by the midpoint rule on a
+    // grid that divides the sphere into cells of equal area.
+    pub fn sphere_integral<F>(f: F) -> f64
+        where F: Fn(Vector) -> f64
+    {
+        let n = 1000;
+        let mut total = 0.0;
+
+        for i in 0..n {
+            let z = 1.0 - 2.0 * (i as f64 + 0.5) / n as f64;
+            let r = (1.0 - z * z).sqrt();
+            for j in 0..n {
+                let phi = 2.0 * PI * (j as f64 + 0.5) / n as f64;
+                total += f(Vector::new(r * phi.cos(), r * phi.sin(), z));
+            }
+        }
+
+        total * 4.0 * PI / (n * n) as f64
+    }
+
+    // How much of the light from wo the material scatters, and the total of
+    // its density, over every direction.
+    pub fn albedo_and_pdf_total(material: &dyn Material, wo: Vector)
+        -> (f64, f64)
+    {
+        let hr = hit(material);
+        let albedo = sphere_integral(|wi| {
+            material.eval(&hr, wo, wi).x * wi.dot(hr.normal).abs()
+        });
+        let pdf_total = sphere_integral(|wi| material.pdf(&hr, wo, wi));
+
+        (albedo, pdf_total)
+    }
+}
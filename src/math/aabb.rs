@@ -0,0 +1,167 @@
+// aabb.rs - Axis-aligned bounding boxes.
+// Written by quadfault
+// 10/18/26
+
+use super::{ Point, Ray, Vector };
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    // The empty box: the union of it with any other box is that other box.
+    pub fn empty() -> Self {
+        Self::new(
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point::new(-f64::INFINITY, -f64::INFINITY, -f64::INFINITY),
+        )
+    }
+
+    pub fn from_points(points: &[Point]) -> Self {
+        points.iter().fold(Self::empty(), |b, &p| b.union_point(p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x
+            || self.min.y > self.max.y
+            || self.min.z > self.max.z
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            Point::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            Point::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        )
+    }
+
+    pub fn union_point(&self, p: Point) -> Self {
+        self.union(&Self::new(p, p))
+    }
+
+    pub fn extent(&self) -> Vector {
+        self.max - self.min
+    }
+
+    pub fn centroid(&self) -> Point {
+        self.min + self.extent() * 0.5
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let e = self.extent();
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
+    // The axis (0 = x, 1 = y, 2 = z) along which the box is longest.
+    pub fn longest_axis(&self) -> usize {
+        let e = self.extent();
+        if e.x >= e.y && e.x >= e.z {
+            0
+        } else if e.y >= e.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> bool {
+        let inv_direction = Vector::new(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+
+        self.hit_inverse(ray.origin, inv_direction, tmin, tmax)
+    }
+
+    // Slab test against a ray given by its origin and the reciprocal of its
+    // direction, so that callers testing many boxes only divide once.
+    pub fn hit_inverse(&self,
+                       origin: Point,
+                       inv_direction: Vector,
+                       mut tmin: f64,
+                       mut tmax: f64)
+        -> bool
+    {
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - origin[axis]) * inv_direction[axis];
+            let t1 = (self.max[axis] - origin[axis]) * inv_direction[axis];
+            let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+
+            // Written so that a NaN (from 0 * inf) leaves the bounds alone.
+            if t0 > tmin {
+                tmin = t0;
+            }
+            if t1 < tmax {
+                tmax = t1;
+            }
+            if tmax < tmin {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union() {
+        let a = Aabb::new(Point::origin(), Point::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(
+            Point::new(-1.0, 0.5, 0.0),
+            Point::new(0.5, 2.0, 1.0),
+        );
+
+        assert_eq!(
+            a.union(&b),
+            Aabb::new(Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 2.0, 1.0)),
+        );
+        assert_eq!(Aabb::empty().union(&a), a);
+    }
+
+    #[test]
+    fn surface_area() {
+        let b = Aabb::new(Point::origin(), Point::new(1.0, 2.0, 3.0));
+
+        assert_eq!(b.surface_area(), 22.0);
+        assert_eq!(Aabb::empty().surface_area(), 0.0);
+    }
+
+    #[test]
+    fn hit() {
+        let b = Aabb::new(
+            Point::new(-1.0, -1.0, -1.0),
+            Point::new(1.0, 1.0, 1.0),
+        );
+        let forward = Vector::new(0.0, 0.0, -1.0);
+        let towards = Ray::new(Point::new(0.0, 0.0, 5.0), forward);
+        let away = Ray::new(Point::new(0.0, 0.0, 5.0), -forward);
+        let beside = Ray::new(Point::new(2.0, 0.0, 5.0), forward);
+
+        assert!(b.hit(&towards, 0.0, f64::MAX));
+        assert!(!b.hit(&towards, 0.0, 3.0));
+        assert!(!b.hit(&away, 0.0, f64::MAX));
+        assert!(!b.hit(&beside, 0.0, f64::MAX));
+    }
+}
@@ -2,11 +2,11 @@
 // Written by quadfault
 // 10/18/18
 
-use std::ops::{ AddAssign, DivAssign, Mul };
+use std::ops::{ Add, AddAssign, DivAssign, Mul };
 
 use super::Vector;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -22,6 +22,11 @@ impl Color {
         Self::new(0.0, 0.0, 0.0)
     }
 
+    // How bright the colour looks, with the Rec. 709 weights.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     pub fn blend(t: f32, start: Self, end: Self) -> Self {
         Self {
             r: (1.0 - t) * start.r + t * end.r,
@@ -31,6 +36,14 @@ impl Color {
     }
 }
 
+impl Add for Color {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
 impl AddAssign for Color {
     fn add_assign(&mut self, rhs: Self) {
         self.r += rhs.r;
@@ -47,6 +60,15 @@ impl DivAssign<f32> for Color {
     }
 }
 
+// Component by component, as when light is filtered by a coloured surface.
+impl Mul for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
 impl Mul<f32> for Color {
     type Output = Self;
 
@@ -2,11 +2,13 @@
 // Written by quadfault
 // 10/20/18
 
+mod aabb;
 mod color;
 mod point;
 mod ray;
 mod vector;
 
+pub use self::aabb::*;
 pub use self::color::*;
 pub use self::point::*;
 pub use self::ray::*;
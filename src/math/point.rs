@@ -2,7 +2,7 @@
 // Written by quadfault
 // 10/19/18
 
-use std::ops::{ Add, Sub };
+use std::ops::{ Add, Index, Sub };
 
 use super::Vector;
 
@@ -35,6 +35,19 @@ impl Add<Vector> for Point {
     }
 }
 
+impl Index<usize> for Point {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Point axis out of range: {}", axis),
+        }
+    }
+}
+
 impl Sub for Point {
     type Output = Vector;
 
@@ -2,7 +2,7 @@
 // Written by quadfault
 // 10/18/18
 
-use std::ops::{ Add, Div, Mul, Neg, Sub };
+use std::ops::{ Add, Div, Index, Mul, Neg, Sub };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vector {
@@ -45,6 +45,24 @@ impl Vector {
             self.x * rhs.y - self.y * rhs.x,
         )
     }
+
+    // Two unit vectors perpendicular to this one, which must be a unit
+    // vector, and to each other, after Duff et al., "Building an Orthonormal
+    // Basis, Revisited" (JCGT, 2017).
+    pub fn perpendiculars(self) -> (Self, Self) {
+        let sign = 1.0f64.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+
+        (
+            Self::new(
+                1.0 + sign * self.x * self.x * a,
+                sign * b,
+                -sign * self.x,
+            ),
+            Self::new(b, sign + self.y * self.y * a, -self.y),
+        )
+    }
 }
 
 impl Add for Vector {
@@ -71,6 +89,19 @@ impl Div<f64> for Vector {
     }
 }
 
+impl Index<usize> for Vector {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vector axis out of range: {}", axis),
+        }
+    }
+}
+
 impl Mul<f64> for Vector {
     type Output = Self;
 
@@ -126,4 +157,21 @@ mod tests {
 
         assert_eq!(v, Vector::new(0.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn perpendiculars() {
+        for &v in &[
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(0.0, 0.0, -1.0),
+            Vector::new(1.0, 2.0, -3.0).hat(),
+        ] {
+            let (a, b) = v.perpendiculars();
+
+            assert!((a.norm() - 1.0).abs() < 1e-12);
+            assert!((b.norm() - 1.0).abs() < 1e-12);
+            assert!(a.dot(v).abs() < 1e-12);
+            assert!(b.dot(v).abs() < 1e-12);
+            assert!(a.dot(b).abs() < 1e-12);
+        }
+    }
 }
@@ -0,0 +1,351 @@
+// models/bvh.rs - Bounding volume hierarchies.
+// Written by quadfault
+// 10/18/26
+
+use crate::math::{ Aabb, Point, Ray, Vector };
+
+use super::HitResult;
+
+const BUCKETS: usize = 12;
+
+// Nodes this small always become leaves; nodes larger than LARGEST_LEAF are
+// always split, even when the heuristic says a leaf would be cheaper.
+const SMALLEST_SPLIT: usize = 5;
+const LARGEST_LEAF: usize = 32;
+
+// Relative costs of visiting an interior node and of intersecting a single
+// primitive, for the surface area heuristic.
+const TRAVERSAL_COST: f64 = 0.125;
+const INTERSECTION_COST: f64 = 1.0;
+
+// A hierarchy over primitives identified by caller-chosen ids. The BVH only
+// knows their bounding boxes; hit() hands ids back to the caller to test.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    ids: Vec<usize>,
+}
+
+enum Node {
+    Leaf {
+        bounds: Aabb,
+        first: usize,
+        count: usize,
+    },
+    // The left child always immediately follows its parent.
+    Interior {
+        bounds: Aabb,
+        right: usize,
+        axis: usize,
+    },
+}
+
+struct Primitive {
+    id: usize,
+    bounds: Aabb,
+    centroid: Point,
+}
+
+impl Bvh {
+    pub fn new(primitives: Vec<(usize, Aabb)>) -> Self {
+        let mut primitives: Vec<_> = primitives.into_iter()
+            .map(|(id, bounds)| Primitive {
+                id,
+                bounds,
+                centroid: bounds.centroid(),
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: vec![],
+            ids: Vec::with_capacity(primitives.len()),
+        };
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives);
+        }
+
+        bvh
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match self.nodes.first() {
+            Some(node) => node.bounds(),
+            None => Aabb::empty(),
+        }
+    }
+
+    // Finds the closest hit along the ray. `hit_primitive` is called with a
+    // primitive id and the current (tmin, tmax) interval.
+    pub fn hit<'a, F>(&self,
+                      ray: &Ray,
+                      tmin: f64,
+                      tmax: f64,
+                      mut hit_primitive: F)
+        -> Option<HitResult<'a>>
+        where F: FnMut(usize, f64, f64) -> Option<HitResult<'a>>
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let inv_direction = Vector::new(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+        let mut closest_so_far = tmax;
+        let mut rc = None;
+        let mut stack = vec![0];
+
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !node.bounds().hit_inverse(
+                ray.origin, inv_direction, tmin, closest_so_far
+            ) {
+                continue;
+            }
+
+            match *node {
+                Node::Leaf { first, count, .. } => {
+                    for &id in &self.ids[first..first + count] {
+                        if let Some(hr) =
+                            hit_primitive(id, tmin, closest_so_far)
+                        {
+                            closest_so_far = hr.t;
+                            rc = Some(hr);
+                        }
+                    }
+                }
+                Node::Interior { right, axis, .. } => {
+                    // Visit the nearer child first so that it can shrink
+                    // closest_so_far before the farther one is tested.
+                    if inv_direction[axis] < 0.0 {
+                        stack.push(i + 1);
+                        stack.push(right);
+                    } else {
+                        stack.push(right);
+                        stack.push(i + 1);
+                    }
+                }
+            }
+        }
+
+        rc
+    }
+
+    fn build(&mut self, primitives: &mut [Primitive]) -> usize {
+        let bounds = primitives.iter()
+            .fold(Aabb::empty(), |b, p| b.union(&p.bounds));
+        let centroid_bounds = primitives.iter()
+            .fold(Aabb::empty(), |b, p| b.union_point(p.centroid));
+        let index = self.nodes.len();
+
+        let axis = centroid_bounds.longest_axis();
+        let split = if primitives.len() < SMALLEST_SPLIT
+            || centroid_bounds.extent()[axis] <= 0.0
+        {
+            None
+        } else {
+            split_by_sah(primitives, &bounds, &centroid_bounds, axis)
+        };
+
+        match split {
+            None => {
+                self.nodes.push(Node::Leaf {
+                    bounds,
+                    first: self.ids.len(),
+                    count: primitives.len(),
+                });
+                self.ids.extend(primitives.iter().map(|p| p.id));
+            }
+            Some(mid) => {
+                self.nodes.push(Node::Interior { bounds, right: 0, axis });
+                let (left, right) = primitives.split_at_mut(mid);
+                self.build(left);
+                let right_index = self.build(right);
+                if let Node::Interior { ref mut right, .. } =
+                    self.nodes[index]
+                {
+                    *right = right_index;
+                }
+            }
+        }
+
+        index
+    }
+}
+
+impl Node {
+    fn bounds(&self) -> Aabb {
+        match *self {
+            Node::Leaf { bounds, .. } => bounds,
+            Node::Interior { bounds, .. } => bounds,
+        }
+    }
+}
+
+// Buckets the primitives by centroid along `axis` and partitions them at the
+// bucket boundary with the lowest surface area heuristic cost. Returns the
+// partition point, or None if no split is cheaper than a single leaf.
+fn split_by_sah(primitives: &mut [Primitive],
+                bounds: &Aabb,
+                centroid_bounds: &Aabb,
+                axis: usize)
+    -> Option<usize>
+{
+    let lo = centroid_bounds.min[axis];
+    let width = centroid_bounds.extent()[axis];
+    let bucket_of = |p: &Primitive| {
+        let b = ((p.centroid[axis] - lo) / width * BUCKETS as f64) as usize;
+        b.min(BUCKETS - 1)
+    };
+
+    let mut counts = [0usize; BUCKETS];
+    let mut bucket_bounds = [Aabb::empty(); BUCKETS];
+    for p in primitives.iter() {
+        let b = bucket_of(p);
+        counts[b] += 1;
+        bucket_bounds[b] = bucket_bounds[b].union(&p.bounds);
+    }
+
+    let mut best_cost = f64::INFINITY;
+    let mut best_split = 0;
+    for split in 1..BUCKETS {
+        let side = |buckets: std::ops::Range<usize>| buckets
+            .fold((Aabb::empty(), 0), |(b, n), i| {
+                (b.union(&bucket_bounds[i]), n + counts[i])
+            });
+        let (left_bounds, left_count) = side(0..split);
+        let (right_bounds, right_count) = side(split..BUCKETS);
+        if left_count == 0 || right_count == 0 {
+            continue;
+        }
+
+        let cost = TRAVERSAL_COST + INTERSECTION_COST
+            * (left_bounds.surface_area() * left_count as f64
+                + right_bounds.surface_area() * right_count as f64)
+            / bounds.surface_area();
+        if cost < best_cost {
+            best_cost = cost;
+            best_split = split;
+        }
+    }
+
+    let leaf_cost = INTERSECTION_COST * primitives.len() as f64;
+    if best_split == 0
+        || (best_cost >= leaf_cost && primitives.len() <= LARGEST_LEAF)
+    {
+        return None;
+    }
+
+    // Partition in place: everything in a bucket below the split goes left.
+    let mut mid = 0;
+    for i in 0..primitives.len() {
+        if bucket_of(&primitives[i]) < best_split {
+            primitives.swap(i, mid);
+            mid += 1;
+        }
+    }
+
+    Some(mid)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use crate::materials::Lambertian;
+    use crate::math::{ Point, Vector };
+    use crate::models::{ Model, Sphere };
+
+    use super::*;
+
+    fn random_spheres(rng: &mut StdRng, n: usize) -> Vec<Sphere> {
+        (0..n)
+            .map(|_| Sphere::new(
+                Point::new(
+                    rng.gen_range(-10.0, 10.0),
+                    rng.gen_range(-10.0, 10.0),
+                    rng.gen_range(-10.0, 10.0),
+                ),
+                rng.gen_range(0.05, 1.0),
+                Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5))),
+            ))
+            .collect()
+    }
+
+    fn random_ray(rng: &mut StdRng) -> Ray {
+        Ray::new(
+            Point::new(
+                rng.gen_range(-15.0, 15.0),
+                rng.gen_range(-15.0, 15.0),
+                rng.gen_range(-15.0, 15.0),
+            ),
+            Vector::new(
+                rng.gen_range(-1.0, 1.0),
+                rng.gen_range(-1.0, 1.0),
+                rng.gen_range(-1.0, 1.0),
+            ),
+        )
+    }
+
+    fn linear_hit(spheres: &[Sphere], ray: &Ray) -> Option<(usize, f64)> {
+        let mut closest_so_far = f64::MAX;
+        let mut rc = None;
+
+        for (i, sphere) in spheres.iter().enumerate() {
+            if let Some(hr) = sphere.hit(ray, 0.001, closest_so_far) {
+                closest_so_far = hr.t;
+                rc = Some((i, hr.t));
+            }
+        }
+
+        rc
+    }
+
+    #[test]
+    fn hits_match_linear_scan() {
+        let mut rng = StdRng::from_seed([7; 32]);
+        let spheres = random_spheres(&mut rng, 500);
+        let bvh = Bvh::new(
+            spheres.iter()
+                .enumerate()
+                .map(|(i, s)| (i, s.bounding_box().unwrap()))
+                .collect()
+        );
+
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let ray = random_ray(&mut rng);
+            let mut hit_id = None;
+            let hr = bvh.hit(&ray, 0.001, f64::MAX, |id, tmin, tmax| {
+                let hr = spheres[id].hit(&ray, tmin, tmax);
+                if hr.is_some() {
+                    hit_id = Some(id);
+                }
+                hr
+            });
+
+            let expected = linear_hit(&spheres, &ray);
+            assert_eq!(hr.map(|hr| (hit_id.unwrap(), hr.t)), expected);
+            if expected.is_some() {
+                hits += 1;
+            }
+        }
+
+        // Make sure the test actually exercised some hits.
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn empty() {
+        let bvh = Bvh::new(vec![]);
+        let ray = Ray::new(Point::origin(), Vector::new(0.0, 0.0, -1.0));
+
+        assert!(bvh.is_empty());
+        assert!(bvh.hit(&ray, 0.0, f64::MAX, |_, _, _| None).is_none());
+    }
+}
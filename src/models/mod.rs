@@ -2,22 +2,66 @@
 // Written by quadfault
 // 10/19/18
 
+mod bvh;
 mod plane;
 mod sphere;
+mod triangle;
+mod triangle_mesh;
 
+pub use self::bvh::*;
 pub use self::plane::*;
 pub use self::sphere::*;
+pub use self::triangle::*;
+pub use self::triangle_mesh::*;
 
 use crate::materials::Material;
-use crate::math::{ Point, Ray, Vector };
+use crate::math::{ Aabb, Point, Ray, Vector };
 
 pub struct HitResult<'a> {
     pub t: f64,
     pub hit_point: Point,
+    // The shading normal, which materials scatter around. On meshes with
+    // vertex normals it is interpolated and can differ from the true
+    // geometric normal of the surface.
     pub normal: Vector,
+    pub geometric_normal: Vector,
+    pub uv: (f64, f64),
     pub material: &'a dyn Material,
 }
 
-pub trait Model {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitResult>;
+// A point picked on a light, to send a shadow ray to. `pdf` is the density
+// by solid angle of the direction towards it from the point being lit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightSample {
+    pub point: Point,
+    pub pdf: f64,
+}
+
+pub trait Model: Send + Sync {
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>;
+
+    // None for models with no finite bounds, like planes.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    // Whether the scene should sample the model as a light: true for models
+    // that give off light and can pick points on themselves. Others, like
+    // glowing planes, still light whatever scatters rays into them.
+    fn is_light(&self) -> bool {
+        false
+    }
+
+    // Picks a point on the model to light `from`, using `u`, two numbers in
+    // [0, 1). None if there is none to pick from there.
+    fn sample_light(&self, _from: Point, _u: (f64, f64))
+        -> Option<LightSample>
+    {
+        None
+    }
+
+    // The density by solid angle with which `sample_light` picks the hit
+    // on the model when lighting `from`.
+    fn light_pdf(&self, _from: Point, _hr: &HitResult) -> f64 {
+        0.0
+    }
 }
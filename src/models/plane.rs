@@ -3,7 +3,7 @@
 // 10/28/18
 
 use crate::materials::Material;
-use crate::math::{ Point, Ray, Vector };
+use crate::math::{ Aabb, Point, Ray, Vector };
 
 use super::{ HitResult, Model };
 
@@ -28,7 +28,9 @@ impl Plane {
 }
 
 impl Model for Plane {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitResult> {
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>
+    {
         let n_dot_d = self.normal.dot(ray.direction);
         if n_dot_d != 0.0 {
             let t = self.normal.dot(self.point_on_plane - ray.origin)
@@ -40,6 +42,8 @@ impl Model for Plane {
                     t,
                     hit_point,
                     normal: self.normal,
+                    geometric_normal: self.normal,
+                    uv: (0.0, 0.0),
                     material: self.material.as_ref(),
                 })
             } else {
@@ -49,4 +53,8 @@ impl Model for Plane {
             None
         }
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
 }
@@ -2,10 +2,12 @@
 // Written by quadfault
 // 10/24/18
 
+use std::f64::consts::{ FRAC_PI_2, PI };
+
 use crate::materials::Material;
-use crate::math::{ Point, Ray };
+use crate::math::{ Aabb, Point, Ray, Vector };
 
-use super::{ HitResult, Model };
+use super::{ HitResult, LightSample, Model };
 
 pub struct Sphere {
     center: Point,
@@ -21,10 +23,27 @@ impl Sphere {
     {
         Self { center, radius, material }
     }
+
+    // One minus the cosine of the half-angle of the cone the sphere fills
+    // as seen from `from`, worked out so as not to cancel to zero when the
+    // sphere is small or far away. None from inside the sphere.
+    fn cone(&self, from: Point) -> Option<f64> {
+        let distance_sqr = (self.center - from).norm_sqr();
+        let sin_sqr = self.radius * self.radius / distance_sqr;
+        if sin_sqr >= 1.0 {
+            return None;
+        }
+
+        let cos_max = (1.0 - sin_sqr).sqrt();
+
+        Some(sin_sqr / (1.0 + cos_max))
+    }
 }
 
 impl Model for Sphere {
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitResult> {
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>
+    {
         let oc = ray.origin - self.center;
         let a = ray.direction.dot(ray.direction);
         let b = oc.dot(ray.direction);
@@ -36,11 +55,14 @@ impl Model for Sphere {
             for &t in &[(-b - dsqrt) / a, (-b + dsqrt) / a] {
                 if tmin < t && t < tmax {
                     let hit_point = ray.at(t);
+                    let normal = (hit_point - self.center) / self.radius;
 
                     return Some(HitResult {
                         t,
                         hit_point,
-                        normal: (hit_point - self.center) / self.radius,
+                        normal,
+                        geometric_normal: normal,
+                        uv: spherical_uv(normal),
                         material: self.material.as_ref(),
                     })
                 }
@@ -49,4 +71,100 @@ impl Model for Sphere {
 
         None
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vector::new(self.radius, self.radius, self.radius);
+
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+
+    fn is_light(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    // Picks a direction uniformly within the cone the sphere fills, rather
+    // than a point on its surface, half of which can't be seen anyway.
+    fn sample_light(&self, from: Point, u: (f64, f64))
+        -> Option<LightSample>
+    {
+        let one_minus_cos_max = self.cone(from)?;
+        let cos_theta = 1.0 - u.0 * one_minus_cos_max;
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * u.1;
+
+        let to_center = self.center - from;
+        let w = to_center.hat();
+        let (a, b) = w.perpendiculars();
+        let direction = a * (sin_theta * phi.cos())
+            + b * (sin_theta * phi.sin())
+            + w * cos_theta;
+
+        // The nearer of the two hits along the direction, which meet at the
+        // edge of the cone.
+        let along = to_center.dot(direction);
+        let c = to_center.norm_sqr() - self.radius * self.radius;
+        let t = along - (along * along - c).max(0.0).sqrt();
+
+        Some(LightSample {
+            point: from + direction * t,
+            pdf: 1.0 / (2.0 * PI * one_minus_cos_max),
+        })
+    }
+
+    fn light_pdf(&self, from: Point, _hr: &HitResult) -> f64 {
+        match self.cone(from) {
+            Some(one_minus_cos_max) => 1.0 / (2.0 * PI * one_minus_cos_max),
+            None => 0.0,
+        }
+    }
+}
+
+// Longitude and latitude of a point on the unit sphere, scaled to [0, 1].
+fn spherical_uv(p: Vector) -> (f64, f64) {
+    let phi = p.z.atan2(p.x);
+    let theta = p.y.clamp(-1.0, 1.0).asin();
+
+    (
+        1.0 - (phi + PI) / (2.0 * PI),
+        (theta + FRAC_PI_2) / PI,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::DiffuseLight;
+    use crate::math::Color;
+    use crate::samplers::{ IndependentSampler, Sampler };
+
+    use super::*;
+
+    #[test]
+    fn light_samples_fill_the_cone() {
+        let sphere = Sphere::new(
+            Point::new(0.0, 0.0, -3.0),
+            1.0,
+            Box::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0))),
+        );
+        let from = Point::origin();
+        let mut sampler = IndependentSampler::new();
+        assert!(sphere.is_light());
+
+        // The sphere fills a cone of half-angle asin(1/3), and the density
+        // is the same across its solid angle.
+        let cone = 2.0 * PI * (1.0 - 8.0f64.sqrt() / 3.0);
+        for i in 0..1000 {
+            sampler.start_pixel_sample(0, 0, i, 1000);
+            let sample = sphere.sample_light(from, sampler.get_2d()).unwrap();
+            assert!((sample.pdf * cone - 1.0).abs() < 1e-9);
+
+            // Each point is the nearest one in its direction.
+            let ray = Ray::new(from, sample.point - from);
+            let hr = sphere.hit(&ray, 0.0, f64::MAX).unwrap();
+            assert!((hr.t - 1.0).abs() < 1e-6);
+            assert_eq!(sphere.light_pdf(from, &hr), sample.pdf);
+        }
+
+        let inside = Point::new(0.0, 0.0, -3.5);
+        assert!(sphere.sample_light(inside, (0.5, 0.5)).is_none());
+    }
 }
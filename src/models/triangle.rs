@@ -0,0 +1,295 @@
+// models/triangle.rs - Triangles.
+// Written by quadfault
+// 10/18/26
+
+use crate::materials::Material;
+use crate::math::{ Aabb, Point, Ray, Vector };
+
+use super::{ HitResult, LightSample, Model };
+
+pub struct Triangle {
+    vertices: [Point; 3],
+    normals: Option<[Vector; 3]>,
+    material: Box<dyn Material>,
+}
+
+impl Triangle {
+    // The front face is the one from which the vertices appear
+    // counter-clockwise.
+    pub fn new(vertices: [Point; 3], material: Box<dyn Material>) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            material,
+        }
+    }
+
+    // A triangle whose shading normal is interpolated between the given
+    // per-vertex normals.
+    pub fn with_normals(vertices: [Point; 3],
+                        normals: [Vector; 3],
+                        material: Box<dyn Material>)
+        -> Self
+    {
+        Self {
+            vertices,
+            normals: Some([
+                normals[0].hat(),
+                normals[1].hat(),
+                normals[2].hat(),
+            ]),
+            material,
+        }
+    }
+}
+
+impl Model for Triangle {
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>
+    {
+        let [p0, p1, p2] = self.vertices;
+        let (t, b) = intersect(ray, p0, p1, p2, tmin, tmax)?;
+        let geometric_normal = geometric_normal(p0, p1, p2);
+        let normal = match self.normals {
+            Some(n) => shading_normal(n[0], n[1], n[2], b, geometric_normal),
+            None => geometric_normal,
+        };
+
+        Some(HitResult {
+            t,
+            hit_point: ray.at(t),
+            normal,
+            geometric_normal,
+            uv: (b[1], b[2]),
+            material: self.material.as_ref(),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(&self.vertices))
+    }
+
+    fn is_light(&self) -> bool {
+        let [p0, p1, p2] = self.vertices;
+
+        self.material.is_emissive() && area(p0, p1, p2) > 0.0
+    }
+
+    fn sample_light(&self, from: Point, u: (f64, f64))
+        -> Option<LightSample>
+    {
+        let [p0, p1, p2] = self.vertices;
+        let point = sample_point(p0, p1, p2, u);
+        let normal = geometric_normal(p0, p1, p2);
+        let pdf = solid_angle_pdf(from, point, normal, area(p0, p1, p2))?;
+
+        Some(LightSample { point, pdf })
+    }
+
+    fn light_pdf(&self, from: Point, hr: &HitResult) -> f64 {
+        let [p0, p1, p2] = self.vertices;
+
+        solid_angle_pdf(from, hr.hit_point, hr.geometric_normal,
+                        area(p0, p1, p2))
+            .unwrap_or(0.0)
+    }
+}
+
+// Watertight ray/triangle intersection, after Woop, Benthin and Wald,
+// "Watertight Ray/Triangle Intersection" (JCGT, 2013). Rays through a shared
+// edge or vertex hit at least one of the triangles that share it. Returns
+// the ray parameter and the barycentric coordinates of the hit.
+pub(crate) fn intersect(ray: &Ray,
+                        p0: Point,
+                        p1: Point,
+                        p2: Point,
+                        tmin: f64,
+                        tmax: f64)
+    -> Option<(f64, [f64; 3])>
+{
+    let d = ray.direction;
+
+    // Permute the axes so that the ray travels mostly along the new z, and
+    // keep the winding the same if that flips the handedness.
+    let kz = if d.x.abs() > d.y.abs() {
+        if d.x.abs() > d.z.abs() { 0 } else { 2 }
+    } else if d.y.abs() > d.z.abs() {
+        1
+    } else {
+        2
+    };
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if d[kz] < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // Shear so that the ray points along +z from the origin.
+    let sx = d[kx] / d[kz];
+    let sy = d[ky] / d[kz];
+    let sz = 1.0 / d[kz];
+
+    let a = p0 - ray.origin;
+    let b = p1 - ray.origin;
+    let c = p2 - ray.origin;
+    let (ax, ay) = (a[kx] - sx * a[kz], a[ky] - sy * a[kz]);
+    let (bx, by) = (b[kx] - sx * b[kz], b[ky] - sy * b[kz]);
+    let (cx, cy) = (c[kx] - sx * c[kz], c[ky] - sy * c[kz]);
+
+    // Scaled barycentric coordinates: twice the signed areas of the
+    // sub-triangles opposite each vertex, as seen down the ray.
+    let u = cx * by - cy * bx;
+    let v = ax * cy - ay * cx;
+    let w = bx * ay - by * ax;
+
+    if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0.0 {
+        return None;
+    }
+
+    let t = (u * sz * a[kz] + v * sz * b[kz] + w * sz * c[kz]) / det;
+    if !(tmin < t && t < tmax) {
+        return None;
+    }
+
+    Some((t, [u / det, v / det, w / det]))
+}
+
+pub(crate) fn geometric_normal(p0: Point, p1: Point, p2: Point) -> Vector {
+    (p1 - p0).cross(p2 - p0).hat()
+}
+
+pub(crate) fn area(p0: Point, p1: Point, p2: Point) -> f64 {
+    0.5 * (p1 - p0).cross(p2 - p0).norm()
+}
+
+// A point picked uniformly by area on the triangle, using `u`.
+pub(crate) fn sample_point(p0: Point, p1: Point, p2: Point, u: (f64, f64))
+    -> Point
+{
+    let s = u.0.sqrt();
+    let b1 = u.1 * s;
+    let b2 = (1.0 - u.1) * s;
+
+    p0 + (p1 - p0) * b1 + (p2 - p0) * b2
+}
+
+// Converts the density of points picked uniformly over `area` to a density
+// by solid angle as seen from `from`, given one such point and the surface
+// normal there. None if the surface is seen edge-on.
+pub(crate) fn solid_angle_pdf(from: Point,
+                              point: Point,
+                              normal: Vector,
+                              area: f64)
+    -> Option<f64>
+{
+    let to_point = point - from;
+    let distance_sqr = to_point.norm_sqr();
+    let cosine = to_point.dot(normal).abs() / distance_sqr.sqrt();
+
+    if cosine * area > 0.0 {
+        Some(distance_sqr / (cosine * area))
+    } else {
+        None
+    }
+}
+
+// Interpolates vertex normals by barycentric coordinates, flipped if need be
+// to lie on the same side of the surface as the geometric normal.
+pub(crate) fn shading_normal(n0: Vector,
+                             n1: Vector,
+                             n2: Vector,
+                             b: [f64; 3],
+                             geometric_normal: Vector)
+    -> Vector
+{
+    let n = (n0 * b[0] + n1 * b[1] + n2 * b[2]).hat();
+
+    if n.dot(geometric_normal) < 0.0 { -n } else { n }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::materials::Lambertian;
+
+    use super::*;
+
+    fn material() -> Box<dyn Material> {
+        Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5)))
+    }
+
+    fn down(x: f64, y: f64) -> Ray {
+        Ray::new(Point::new(x, y, 1.0), Vector::new(0.0, 0.0, -1.0))
+    }
+
+    #[test]
+    fn hit() {
+        let triangle = Triangle::new(
+            [
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            material(),
+        );
+
+        let hr = triangle.hit(&down(0.25, 0.5), 0.0, f64::MAX).unwrap();
+        assert!((hr.t - 1.0).abs() < 1e-12);
+        assert_eq!(hr.geometric_normal, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(hr.normal, hr.geometric_normal);
+        assert!((hr.uv.0 - 0.25).abs() < 1e-12);
+        assert!((hr.uv.1 - 0.5).abs() < 1e-12);
+
+        assert!(triangle.hit(&down(0.75, 0.75), 0.0, f64::MAX).is_none());
+        assert!(triangle.hit(&down(0.25, 0.5), 0.0, 0.5).is_none());
+    }
+
+    #[test]
+    fn shared_edges_are_watertight() {
+        let (p0, p1, p2, p3) = (
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+            Point::new(0.0, 1.0, 0.0),
+        );
+
+        // Rays straight down the diagonal both triangles share.
+        for i in 0..=100 {
+            let s = i as f64 / 100.0;
+            let ray = down(s, s);
+            let first = intersect(&ray, p0, p1, p2, 0.0, f64::MAX);
+            let second = intersect(&ray, p0, p2, p3, 0.0, f64::MAX);
+
+            assert!(first.is_some() || second.is_some(), "miss at {}", s);
+        }
+    }
+
+    #[test]
+    fn interpolated_normals() {
+        let triangle = Triangle::with_normals(
+            [
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            [
+                Vector::new(-1.0, 0.0, 1.0),
+                Vector::new(1.0, 0.0, 1.0),
+                Vector::new(1.0, 0.0, 1.0),
+            ],
+            material(),
+        );
+
+        let hr = triangle.hit(&down(0.5, 0.0), 0.0, f64::MAX).unwrap();
+        assert_eq!(hr.geometric_normal, Vector::new(0.0, 0.0, 1.0));
+        assert!((hr.normal - Vector::new(0.0, 0.0, 1.0)).norm() < 1e-12);
+
+        let hr = triangle.hit(&down(0.9, 0.05), 0.0, f64::MAX).unwrap();
+        assert!(hr.normal.x > 0.0);
+        assert!((hr.normal.norm() - 1.0).abs() < 1e-12);
+    }
+}
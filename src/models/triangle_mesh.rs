@@ -0,0 +1,369 @@
+// models/triangle_mesh.rs - Indexed triangle meshes.
+// Written by quadfault
+// 10/18/26
+
+use crate::materials::Material;
+use crate::math::{ Aabb, Point, Ray, Vector };
+
+use super::{ Bvh, HitResult, LightSample, Model };
+use super::triangle::{
+    area,
+    geometric_normal,
+    intersect,
+    sample_point,
+    shading_normal,
+    solid_angle_pdf,
+};
+
+// A corner of a triangle, as indices into the mesh's buffers. Positions,
+// normals and texture coordinates are indexed separately, so corners can
+// share a position but not a normal (as along a hard edge.)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVertex {
+    pub position: usize,
+    pub normal: Option<usize>,
+    pub uv: Option<usize>,
+}
+
+pub struct TriangleMesh {
+    positions: Vec<Point>,
+    normals: Vec<Vector>,
+    uvs: Vec<(f64, f64)>,
+    triangles: Vec<[MeshVertex; 3]>,
+    material: Box<dyn Material>,
+    bvh: Bvh,
+    // The running total of the triangles' areas, for picking them by area
+    // when the mesh is a light.
+    cumulative_areas: Vec<f64>,
+}
+
+impl MeshVertex {
+    pub fn new(position: usize) -> Self {
+        Self {
+            position,
+            normal: None,
+            uv: None,
+        }
+    }
+}
+
+impl TriangleMesh {
+    // Panics if any triangle refers past the end of a buffer.
+    pub fn new(positions: Vec<Point>,
+               normals: Vec<Vector>,
+               uvs: Vec<(f64, f64)>,
+               triangles: Vec<[MeshVertex; 3]>,
+               material: Box<dyn Material>)
+        -> Self
+    {
+        for vertex in triangles.iter().flatten() {
+            assert!(vertex.position < positions.len(),
+                    "mesh position index {} out of range", vertex.position);
+            if let Some(n) = vertex.normal {
+                assert!(n < normals.len(),
+                        "mesh normal index {} out of range", n);
+            }
+            if let Some(uv) = vertex.uv {
+                assert!(uv < uvs.len(), "mesh uv index {} out of range", uv);
+            }
+        }
+
+        let bvh = Bvh::new(
+            triangles.iter()
+                .enumerate()
+                .map(|(i, tri)| (i, Aabb::from_points(&[
+                    positions[tri[0].position],
+                    positions[tri[1].position],
+                    positions[tri[2].position],
+                ])))
+                .collect()
+        );
+        let cumulative_areas = triangles.iter()
+            .scan(0.0, |total, tri| {
+                *total += area(
+                    positions[tri[0].position],
+                    positions[tri[1].position],
+                    positions[tri[2].position],
+                );
+                Some(*total)
+            })
+            .collect();
+
+        Self {
+            positions,
+            normals: normals.into_iter().map(|n| n.hat()).collect(),
+            uvs,
+            triangles,
+            material,
+            bvh,
+            cumulative_areas,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    fn total_area(&self) -> f64 {
+        self.cumulative_areas.last().cloned().unwrap_or(0.0)
+    }
+
+    fn vertices(&self, i: usize) -> (Point, Point, Point) {
+        let [v0, v1, v2] = self.triangles[i];
+
+        (
+            self.positions[v0.position],
+            self.positions[v1.position],
+            self.positions[v2.position],
+        )
+    }
+
+    fn hit_triangle(&self, i: usize, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>
+    {
+        let [v0, v1, v2] = self.triangles[i];
+        let (p0, p1, p2) = self.vertices(i);
+        let (t, b) = intersect(ray, p0, p1, p2, tmin, tmax)?;
+
+        let geometric_normal = geometric_normal(p0, p1, p2);
+        let normal = match (v0.normal, v1.normal, v2.normal) {
+            (Some(n0), Some(n1), Some(n2)) => shading_normal(
+                self.normals[n0],
+                self.normals[n1],
+                self.normals[n2],
+                b,
+                geometric_normal,
+            ),
+            _ => geometric_normal,
+        };
+        let uv = match (v0.uv, v1.uv, v2.uv) {
+            (Some(uv0), Some(uv1), Some(uv2)) => {
+                let uv0 = self.uvs[uv0];
+                let uv1 = self.uvs[uv1];
+                let uv2 = self.uvs[uv2];
+
+                (
+                    b[0] * uv0.0 + b[1] * uv1.0 + b[2] * uv2.0,
+                    b[0] * uv0.1 + b[1] * uv1.1 + b[2] * uv2.1,
+                )
+            }
+            _ => (b[1], b[2]),
+        };
+
+        Some(HitResult {
+            t,
+            hit_point: ray.at(t),
+            normal,
+            geometric_normal,
+            uv,
+            material: self.material.as_ref(),
+        })
+    }
+}
+
+impl Model for TriangleMesh {
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<HitResult<'_>>
+    {
+        self.bvh.hit(ray, tmin, tmax, |i, tmin, tmax| {
+            self.hit_triangle(i, ray, tmin, tmax)
+        })
+    }
+
+    // An empty mesh has no bounds to put in the scene's BVH.
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.bvh.bounds())
+        }
+    }
+
+    fn is_light(&self) -> bool {
+        self.material.is_emissive() && self.total_area() > 0.0
+    }
+
+    // Picks a triangle by area with the first number, then reuses what's
+    // left of it to pick a point on that triangle, so that every point on
+    // the mesh is as likely as any other.
+    fn sample_light(&self, from: Point, u: (f64, f64))
+        -> Option<LightSample>
+    {
+        let total = self.total_area();
+        let target = u.0 * total;
+        let i = self.cumulative_areas
+            .partition_point(|&sum| sum <= target)
+            .min(self.triangles.len().checked_sub(1)?);
+        let start = if i == 0 { 0.0 } else { self.cumulative_areas[i - 1] };
+        let area = self.cumulative_areas[i] - start;
+        if area <= 0.0 {
+            return None;
+        }
+
+        let (p0, p1, p2) = self.vertices(i);
+        let rest = ((target - start) / area).min(1.0);
+        let point = sample_point(p0, p1, p2, (rest, u.1));
+        let normal = geometric_normal(p0, p1, p2);
+        let pdf = solid_angle_pdf(from, point, normal, total)?;
+
+        Some(LightSample { point, pdf })
+    }
+
+    fn light_pdf(&self, from: Point, hr: &HitResult) -> f64 {
+        solid_angle_pdf(from, hr.hit_point, hr.geometric_normal,
+                        self.total_area())
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::materials::{ DiffuseLight, Lambertian };
+    use crate::math::Color;
+    use crate::samplers::{ IndependentSampler, Sampler };
+
+    use super::*;
+
+    fn square() -> TriangleMesh {
+        square_of(Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5))))
+    }
+
+    // A unit square in the z = 0 plane, split along its diagonal, with
+    // normals tilted outwards at the corners like a smooth dome.
+    fn square_of(material: Box<dyn Material>) -> TriangleMesh {
+        let corner = |position| MeshVertex {
+            position,
+            normal: Some(position),
+            uv: Some(position),
+        };
+
+        TriangleMesh::new(
+            vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            vec![
+                Vector::new(-1.0, -1.0, 1.0),
+                Vector::new(1.0, -1.0, 1.0),
+                Vector::new(1.0, 1.0, 1.0),
+                Vector::new(-1.0, 1.0, 1.0),
+            ],
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![
+                [corner(0), corner(1), corner(2)],
+                [corner(0), corner(2), corner(3)],
+            ],
+            material,
+        )
+    }
+
+    fn down(x: f64, y: f64) -> Ray {
+        Ray::new(Point::new(x, y, 1.0), Vector::new(0.0, 0.0, -1.0))
+    }
+
+    #[test]
+    fn hit() {
+        let mesh = square();
+
+        for &(x, y) in &[(0.2, 0.7), (0.7, 0.2), (0.5, 0.5)] {
+            let hr = mesh.hit(&down(x, y), 0.0, f64::MAX).unwrap();
+
+            assert!((hr.t - 1.0).abs() < 1e-12);
+            assert_eq!(hr.geometric_normal, Vector::new(0.0, 0.0, 1.0));
+            assert!((hr.uv.0 - x).abs() < 1e-12);
+            assert!((hr.uv.1 - y).abs() < 1e-12);
+        }
+        assert!(mesh.hit(&down(1.5, 0.5), 0.0, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn shading_normals_are_interpolated() {
+        let mesh = square();
+
+        let centre = mesh.hit(&down(0.5, 0.5), 0.0, f64::MAX).unwrap();
+        assert!((centre.normal - Vector::new(0.0, 0.0, 1.0)).norm() < 1e-12);
+
+        let corner = mesh.hit(&down(0.99, 0.98), 0.0, f64::MAX).unwrap();
+        assert!(corner.normal.x > 0.5 && corner.normal.y > 0.5);
+        assert!((corner.normal.norm() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn light_samples_are_uniform_by_area() {
+        let lamp = DiffuseLight::new(Color::new(1.0, 1.0, 1.0));
+        let mesh = square_of(Box::new(lamp));
+        let from = Point::new(0.5, 0.5, 1.0);
+        let mut sampler = IndependentSampler::new();
+        assert!(mesh.is_light());
+        assert!(!square().is_light());
+
+        let n = 10000;
+        let mut left = 0;
+        for i in 0..n {
+            sampler.start_pixel_sample(0, 0, i, n);
+            let sample = mesh.sample_light(from, sampler.get_2d()).unwrap();
+            if sample.point.x < 0.5 {
+                left += 1;
+            }
+
+            let ray = Ray::new(from, sample.point - from);
+            let hr = mesh.hit(&ray, 0.0, f64::MAX).unwrap();
+            assert!((hr.t - 1.0).abs() < 1e-9);
+            assert!((mesh.light_pdf(from, &hr) - sample.pdf).abs() < 1e-9);
+        }
+        assert!((left as f64 / n as f64 - 0.5).abs() < 0.02);
+
+        // Over every direction, the density adds up to one.
+        let mut total = 0.0;
+        for i in 0..n {
+            sampler.start_pixel_sample(1, 0, i, n);
+            let (a, b) = sampler.get_2d();
+            let z = 1.0 - 2.0 * a;
+            let r = (1.0 - z * z).sqrt();
+            let phi = 2.0 * PI * b;
+            let direction = Vector::new(r * phi.cos(), r * phi.sin(), z);
+
+            let ray = Ray::new(from, direction);
+            if let Some(hr) = mesh.hit(&ray, 0.0, f64::MAX) {
+                total += mesh.light_pdf(from, &hr);
+            }
+        }
+        let integral = total * 4.0 * PI / n as f64;
+        assert!((integral - 1.0).abs() < 0.05, "integral {}", integral);
+    }
+
+    #[test]
+    fn empty_meshes_have_no_bounds() {
+        let mesh = TriangleMesh::new(
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5))),
+        );
+
+        assert_eq!(mesh.bounding_box(), None);
+        assert!(mesh.hit(&down(0.5, 0.5), 0.0, f64::MAX).is_none());
+        assert!(square().bounding_box().is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_range() {
+        TriangleMesh::new(
+            vec![Point::origin()],
+            vec![],
+            vec![],
+            vec![[MeshVertex::new(0), MeshVertex::new(1), MeshVertex::new(0)]],
+            Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5))),
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// samplers/halton_sampler.rs - Samplers using the Halton sequence.
+// Written by quadfault
+// 10/18/26
+
+use super::{ ONE_MINUS_EPSILON, SampleState, Sampler, to_unit };
+
+// The Halton sequence takes dimension d of sample i from the digits of i in
+// the dth prime base, reversed after the radix point. The first n samples
+// of each dimension are spread evenly, as are those of pairs of dimensions.
+// Each pixel shifts its samples by a random amount (a Cranley-Patterson
+// rotation) so that neighbouring pixels don't look alike.
+#[derive(Clone, Debug)]
+pub struct HaltonSampler {
+    state: SampleState,
+}
+
+// Past these dimensions, the sequence's bases get large enough that its
+// first few samples line up badly, so it falls back on random numbers.
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53,
+    59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131,
+];
+
+impl HaltonSampler {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: SampleState::new(seed) }
+    }
+}
+
+impl Default for HaltonSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self,
+                          x: usize,
+                          y: usize,
+                          index: usize,
+                          count: usize)
+    {
+        self.state.start(x, y, index, count);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let state = &mut self.state;
+        let dimension = state.next_dimension();
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                let shift = to_unit(state.hash(dimension, 0));
+                let x = radical_inverse(state.index as u64, base) + shift;
+                (x - x.floor()).min(ONE_MINUS_EPSILON)
+            }
+            None => state.random(dimension, 0),
+        }
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+// Reflects the digits of `i` in `base` about the radix point.
+pub fn radical_inverse(mut i: u64, base: u64) -> f64 {
+    let inverse_base = 1.0 / base as f64;
+    let mut digits = 0;
+    let mut scale = 1.0;
+
+    while i > 0 {
+        digits = digits * base + i % base;
+        scale *= inverse_base;
+        i /= base;
+    }
+
+    (digits as f64 * scale).min(ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{ one_per_cell, samples_2d };
+    use super::*;
+
+    #[test]
+    fn radical_inverses() {
+        assert_eq!(radical_inverse(0, 2), 0.0);
+        assert_eq!(radical_inverse(1, 2), 0.5);
+        assert_eq!(radical_inverse(6, 2), 0.375);
+        assert!((radical_inverse(5, 3) - 7.0 / 9.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn first_dimensions_are_stratified() {
+        // The first 2^3 3^2 = 72 samples put one point in each cell of an
+        // 8 by 9 grid, before the pixel's shift. (Nudged up, so that thirds
+        // rounded down still land in the right cell.)
+        let points: Vec<_> = (0..72)
+            .map(|i| {
+                (radical_inverse(i, 2), radical_inverse(i, 3) + 1e-12)
+            })
+            .collect();
+        assert!(one_per_cell(&points, 8, 9));
+
+        // Shifting a whole number of periods keeps the spacing even: 64
+        // samples of the first dimension and 81 of the second are evenly
+        // spaced around the unit circle.
+        let mut sampler = HaltonSampler::new();
+        let xs: Vec<_> = samples_2d(&mut sampler, 64, 0).iter()
+            .map(|p| p.0)
+            .collect();
+        let ys: Vec<_> = samples_2d(&mut sampler, 81, 0).iter()
+            .map(|p| p.1)
+            .collect();
+        for (mut values, n) in [(xs, 64.0), (ys, 81.0)] {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            for w in values.windows(2) {
+                assert!((w[1] - w[0] - 1.0 / n).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// samplers/independent_sampler.rs - Samplers that know nothing of other
+// samples.
+// Written by quadfault
+// 10/18/26
+
+use super::{ SampleState, Sampler };
+
+// Plain uniform random numbers. Converges the slowest, but is the easiest
+// to trust.
+#[derive(Clone, Debug)]
+pub struct IndependentSampler {
+    state: SampleState,
+}
+
+impl IndependentSampler {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: SampleState::new(seed) }
+    }
+}
+
+impl Default for IndependentSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self,
+                          x: usize,
+                          y: usize,
+                          index: usize,
+                          count: usize)
+    {
+        self.state.start(x, y, index, count);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.next_dimension();
+
+        self.state.random(dimension, 0)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let dimension = self.state.next_dimension();
+
+        (self.state.random(dimension, 0), self.state.random(dimension, 1))
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
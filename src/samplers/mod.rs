@@ -0,0 +1,228 @@
+// samplers/mod.rs - Samplers, which choose the random numbers that decide
+// where rays go.
+// Written by quadfault
+// 10/18/26
+
+mod halton_sampler;
+mod independent_sampler;
+mod sobol_sampler;
+mod stratified_sampler;
+
+pub use self::halton_sampler::*;
+pub use self::independent_sampler::*;
+pub use self::sobol_sampler::*;
+pub use self::stratified_sampler::*;
+
+// Each sample of a pixel is a point in many dimensions: two to pick a point
+// in the pixel, maybe two more for the lens, and a few for each bounce.
+// Samplers hand them out one or two at a time, in the order they're asked
+// for, so that the same dimension of every sample in a pixel comes from the
+// same well-spread set of numbers. All samples are in [0, 1).
+//
+// Every number is worked out from the sampler's seed, the pixel, the sample
+// and the dimension alone, never from a stream shared between pixels, so
+// the same seed gives the same image however the work is split up.
+pub trait Sampler: Send + Sync {
+    // Starts sample `index` of the `count` that pixel (x, y) will take.
+    // Samplers that spread samples over the whole pixel may cope poorly
+    // with more than `count` samples, but must still give some.
+    fn start_pixel_sample(&mut self,
+                          x: usize,
+                          y: usize,
+                          index: usize,
+                          count: usize);
+
+    fn get_1d(&mut self) -> f64;
+    fn get_2d(&mut self) -> (f64, f64);
+
+    // A copy of the sampler, with the same seed, say for another thread.
+    fn clone_box(&self) -> Box<dyn Sampler>;
+}
+
+// The largest f64 less than 1.
+const ONE_MINUS_EPSILON: f64 = 1.0 - f64::EPSILON / 2.0;
+
+// Where a sampler is up to: the pixel sample it's on, and the next
+// dimension to hand out.
+#[derive(Clone, Copy, Debug, Default)]
+struct SampleState {
+    seed: u64,
+    x: usize,
+    y: usize,
+    index: usize,
+    count: usize,
+    dimension: usize,
+}
+
+impl SampleState {
+    fn new(seed: u64) -> Self {
+        Self { seed, ..Self::default() }
+    }
+
+    fn start(&mut self, x: usize, y: usize, index: usize, count: usize) {
+        let count = count.max(1);
+        *self = Self { seed: self.seed, x, y, index, count, dimension: 0 };
+    }
+
+    // Takes the next dimension.
+    fn next_dimension(&mut self) -> usize {
+        self.dimension += 1;
+        self.dimension - 1
+    }
+
+    // A hash of the seed, the pixel, a dimension and anything else, for
+    // scrambling samples so that neighbouring pixels don't repeat each other.
+    fn hash(&self, dimension: usize, extra: u64) -> u64 {
+        let (x, y) = (self.x as u64, self.y as u64);
+
+        hash(&[self.seed, x, y, dimension as u64, extra])
+    }
+
+    // A uniform random number for the current sample, the `n`th of those
+    // for a dimension.
+    fn random(&self, dimension: usize, n: u64) -> f64 {
+        let (x, y) = (self.x as u64, self.y as u64);
+        let index = self.index as u64;
+
+        to_unit(hash(&[self.seed, x, y, index, dimension as u64, n]))
+    }
+}
+
+// Mixes the values together into well-scrambled bits, using the finaliser
+// from SplitMix64.
+fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e37_79b9_7f4a_7c15, |h, &v| {
+        let mut z = (h ^ v).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+// Turns random bits into a number in [0, 1).
+fn to_unit(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Shuffles 0..len: maps each i below len to a different number below len,
+// chosen by the seed. From Kensler's "Correlated Multi-Jittered Sampling".
+fn permute(mut i: u32, len: u32, seed: u32) -> u32 {
+    let p = seed;
+    let mut w = len.saturating_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < len {
+            break;
+        }
+    }
+
+    (i.wrapping_add(p)) % len.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutations() {
+        for &len in &[1, 2, 7, 16, 100] {
+            for seed in 0..20 {
+                let mut seen: Vec<_> = (0..len)
+                    .map(|i| permute(i, len, hash(&[seed]) as u32))
+                    .collect();
+                seen.sort();
+
+                assert_eq!(seen, (0..len).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn unit_range() {
+        assert_eq!(to_unit(0), 0.0);
+        assert!(to_unit(u64::MAX) < 1.0);
+        assert_eq!(ONE_MINUS_EPSILON + f64::EPSILON / 2.0, 1.0);
+    }
+
+    #[test]
+    fn seeds_choose_the_samples() {
+        let samplers = |seed| -> Vec<Box<dyn Sampler>> {
+            vec![
+                Box::new(IndependentSampler::with_seed(seed)),
+                Box::new(StratifiedSampler::with_seed(seed)),
+                Box::new(HaltonSampler::with_seed(seed)),
+                Box::new(SobolSampler::with_seed(seed)),
+            ]
+        };
+
+        for ((mut a, mut b), mut c) in samplers(1).into_iter()
+            .zip(samplers(1))
+            .zip(samplers(2))
+        {
+            let points = samples_2d(&mut *a, 16, 1);
+            assert_eq!(points, samples_2d(&mut *b, 16, 1));
+            assert_ne!(points, samples_2d(&mut *c, 16, 1));
+
+            // Clones keep the seed, and taking other pixels first makes no
+            // difference.
+            let mut d = a.clone_box();
+            samples_2d(&mut *a, 4, 0);
+            assert_eq!(points, samples_2d(&mut *a, 16, 1));
+            assert_eq!(points, samples_2d(&mut *d, 16, 1));
+        }
+    }
+
+    // Takes every sample of one pixel, returning a dimension pair of each.
+    pub fn samples_2d(sampler: &mut dyn Sampler,
+                      count: usize,
+                      pair: usize)
+        -> Vec<(f64, f64)>
+    {
+        (0..count)
+            .map(|i| {
+                sampler.start_pixel_sample(3, 5, i, count);
+                for _ in 0..pair {
+                    sampler.get_2d();
+                }
+                sampler.get_2d()
+            })
+            .collect()
+    }
+
+    // Checks that there's one point in each cell of a grid.
+    pub fn one_per_cell(points: &[(f64, f64)], nx: usize, ny: usize) -> bool {
+        let mut cells = vec![0; nx * ny];
+        for &(x, y) in points {
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            let cx = (x * nx as f64) as usize;
+            let cy = (y * ny as f64) as usize;
+            cells[cy * nx + cx] += 1;
+        }
+
+        cells.iter().all(|&n| n == 1)
+    }
+}
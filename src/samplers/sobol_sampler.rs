@@ -0,0 +1,153 @@
+// samplers/sobol_sampler.rs - Samplers using the Sobol' (0, 2)-sequence.
+// Written by quadfault
+// 10/18/26
+
+use super::{ SampleState, Sampler, permute };
+
+// The first two dimensions of the Sobol' sequence form a (0, 2)-sequence:
+// any power of two samples starting from zero put exactly one point in
+// every box of that area whose sides are powers of two, however tall and
+// thin. Every pair of dimensions gets its own copy of the sequence,
+// scrambled with random bits flipped (which keeps that property) and its
+// samples shuffled, so that pairs don't move in step. Single dimensions
+// use the first half of a pair.
+//
+// Best with a power of two samples per pixel.
+#[derive(Clone, Debug)]
+pub struct SobolSampler {
+    state: SampleState,
+}
+
+impl SobolSampler {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: SampleState::new(seed) }
+    }
+
+    // The shuffled index into the sequence, and the bits to flip, for the
+    // next dimension.
+    fn next(&mut self) -> (u32, u64) {
+        let state = &mut self.state;
+        let dimension = state.next_dimension();
+        let count = state.count;
+        let round = state.index / count;
+        let seed = state.hash(dimension, round as u64);
+
+        let shuffled = permute((state.index % count) as u32, count as u32,
+                               seed as u32);
+        ((round * count) as u32 + shuffled, seed)
+    }
+}
+
+impl Default for SobolSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self,
+                          x: usize,
+                          y: usize,
+                          index: usize,
+                          count: usize)
+    {
+        self.state.start(x, y, index, count);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let (i, scramble) = self.next();
+
+        to_unit_32(van_der_corput(i) ^ scramble as u32)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let (i, scramble) = self.next();
+
+        (
+            to_unit_32(van_der_corput(i) ^ scramble as u32),
+            to_unit_32(sobol_2(i) ^ (scramble >> 32) as u32),
+        )
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+// The first dimension of the Sobol' sequence, as 32 bits after the radix
+// point: i's bits in reverse.
+pub fn van_der_corput(i: u32) -> u32 {
+    i.reverse_bits()
+}
+
+// The second dimension.
+pub fn sobol_2(mut i: u32) -> u32 {
+    let mut v = 1 << 31;
+    let mut bits = 0;
+
+    while i != 0 {
+        if i & 1 != 0 {
+            bits ^= v;
+        }
+        i >>= 1;
+        v ^= v >> 1;
+    }
+
+    bits
+}
+
+fn to_unit_32(bits: u32) -> f64 {
+    bits as f64 / (1u64 << 32) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{ one_per_cell, samples_2d };
+    use super::*;
+
+    // Checks every way of splitting the unit square into 2^m boxes that are
+    // powers of two wide and tall.
+    fn is_net(points: &[(f64, f64)]) -> bool {
+        let m = points.len().trailing_zeros();
+
+        (0..=m).all(|a| one_per_cell(points, 1 << a, 1 << (m - a)))
+    }
+
+    #[test]
+    fn sequence() {
+        let first: Vec<_> = (0..4).map(sobol_2).collect();
+        assert_eq!(first, [0, 1 << 31, 3 << 30, 1 << 30]);
+
+        let points: Vec<_> = (0..256)
+            .map(|i| (to_unit_32(van_der_corput(i)), to_unit_32(sobol_2(i))))
+            .collect();
+        for m in 0..=8 {
+            assert!(is_net(&points[..1 << m]));
+        }
+    }
+
+    #[test]
+    fn scrambled_pairs_are_nets() {
+        let mut sampler = SobolSampler::new();
+
+        for &count in &[1, 16, 128] {
+            for pair in 0..5 {
+                assert!(is_net(&samples_2d(&mut sampler, count, pair)));
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_differ() {
+        let mut sampler = SobolSampler::new();
+
+        assert_ne!(
+            samples_2d(&mut sampler, 16, 0),
+            samples_2d(&mut sampler, 16, 1),
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// samplers/stratified_sampler.rs - Jittered samplers.
+// Written by quadfault
+// 10/18/26
+
+use super::{ ONE_MINUS_EPSILON, SampleState, Sampler, permute };
+
+// Splits each dimension (or pair of dimensions) into as many equal strata
+// as there are samples in the pixel, and puts one sample at a random place
+// in each. The strata are shuffled differently for each dimension, so that
+// dimensions don't move in step.
+#[derive(Clone, Debug)]
+pub struct StratifiedSampler {
+    state: SampleState,
+}
+
+impl StratifiedSampler {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: SampleState::new(seed) }
+    }
+
+    // Which stratum, of `count`, the current sample goes in for the next
+    // dimension, and the dimension. Samples past the count start another
+    // round of strata.
+    fn stratum(&mut self) -> (u32, usize) {
+        let state = &mut self.state;
+        let dimension = state.next_dimension();
+        let round = (state.index / state.count) as u64;
+        let seed = state.hash(dimension, round) as u32;
+        let index = (state.index % state.count) as u32;
+
+        (permute(index, state.count as u32, seed), dimension)
+    }
+}
+
+impl Default for StratifiedSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self,
+                          x: usize,
+                          y: usize,
+                          index: usize,
+                          count: usize)
+    {
+        self.state.start(x, y, index, count);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let (stratum, dimension) = self.stratum();
+        let jitter = self.state.random(dimension, 0);
+        let count = self.state.count as f64;
+
+        ((stratum as f64 + jitter) / count).min(ONE_MINUS_EPSILON)
+    }
+
+    // The strata are a grid as close to square as the count allows.
+    fn get_2d(&mut self) -> (f64, f64) {
+        let (nx, ny) = grid(self.state.count);
+        let (stratum, dimension) = self.stratum();
+        let stratum = stratum as usize;
+        let (sx, sy) = ((stratum % nx) as f64, (stratum / nx) as f64);
+        let jitter_x = self.state.random(dimension, 0);
+        let jitter_y = self.state.random(dimension, 1);
+
+        (
+            ((sx + jitter_x) / nx as f64).min(ONE_MINUS_EPSILON),
+            ((sy + jitter_y) / ny as f64).min(ONE_MINUS_EPSILON),
+        )
+    }
+
+    fn clone_box(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
+
+// Factors `count` into a grid's columns and rows, with no more columns
+// than rows.
+fn grid(count: usize) -> (usize, usize) {
+    let mut nx = (count as f64).sqrt() as usize;
+    while count % nx != 0 {
+        nx -= 1;
+    }
+
+    (nx, count / nx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{ one_per_cell, samples_2d };
+    use super::*;
+
+    #[test]
+    fn grids() {
+        assert_eq!(grid(1), (1, 1));
+        assert_eq!(grid(16), (4, 4));
+        assert_eq!(grid(12), (3, 4));
+        assert_eq!(grid(7), (1, 7));
+    }
+
+    #[test]
+    fn one_sample_per_stratum() {
+        let mut sampler = StratifiedSampler::new();
+
+        for &count in &[16, 12, 5] {
+            let (nx, ny) = grid(count);
+            for pair in 0..4 {
+                let points = samples_2d(&mut sampler, count, pair);
+                assert!(one_per_cell(&points, nx, ny));
+            }
+
+            let values: Vec<_> = (0..count)
+                .map(|i| {
+                    sampler.start_pixel_sample(0, 0, i, count);
+                    sampler.get_1d()
+                })
+                .map(|x| (x, 0.5))
+                .collect();
+            assert!(one_per_cell(&values, count, 1));
+        }
+    }
+
+    #[test]
+    fn dimensions_are_shuffled() {
+        let mut sampler = StratifiedSampler::new();
+        let points = samples_2d(&mut sampler, 64, 0);
+        let next = samples_2d(&mut sampler, 64, 1);
+
+        // If the pairs went in step, each sample would land in the same
+        // cell of both.
+        let same = points.iter().zip(&next)
+            .filter(|&(a, b)| {
+                (a.0 * 8.0) as usize == (b.0 * 8.0) as usize
+                    && (a.1 * 8.0) as usize == (b.1 * 8.0) as usize
+            })
+            .count();
+        assert!(same < 8);
+    }
+}
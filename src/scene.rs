@@ -2,128 +2,821 @@
 // Written by quadfault
 // 10/19/18
 
-use rand::prelude::*;
+use std::sync::{ Mutex, OnceLock, mpsc };
+use std::thread;
+use std::time::{ Duration, Instant };
 
+use crate::backgrounds::{ Background, Sky };
 use crate::cameras::Camera;
-use crate::math::{ Color, Ray };
-use crate::models::{ HitResult, Model };
+use crate::film::{ Film, FilmTile };
+use crate::filters::{ BoxFilter, Filter };
+use crate::image::Image;
+use crate::lights::Light;
+use crate::math::{ Color, Point, Ray, Vector };
+use crate::models::{ Bvh, HitResult, Model };
+use crate::samplers::{ Sampler, SobolSampler };
+use crate::tile::Tile;
+
+const TILE_SIZE: usize = 32;
 
 pub struct Scene {
     camera: Box<dyn Camera>,
     models: Vec<Box<dyn Model>>,
+    lights: Vec<Box<dyn Light>>,
+    background: Box<dyn Background>,
+    max_depth: i32,
+    threads: usize,
+    sampler: Box<dyn Sampler>,
+    filter: Box<dyn Filter>,
+    adaptive: Option<AdaptiveSampling>,
+    accelerator: OnceLock<Accelerator>,
+}
+
+// Adaptive sampling takes the camera's samples per pixel in batches, and
+// stops once the standard error of a pixel's mean brightness is below
+// `threshold` times that brightness, or after `max_samples`. Dark pixels
+// are judged as if they had a brightness of at least MIN_BRIGHTNESS.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    pub max_samples: usize,
+    pub threshold: f32,
+}
+
+const MIN_BRIGHTNESS: f32 = 0.01;
+
+// Progressive rendering takes `pass_samples` samples of every pixel at a
+// time, stopping early if given a time limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progressive {
+    pub pass_samples: usize,
+    pub time_limit: Option<Duration>,
+}
+
+// How far a progressive render has got.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    pub passes: usize,
+    pub samples_per_pixel: usize,
+    pub elapsed: Duration,
+}
+
+// Which samples of each pixel to take, `batch` at a time, stopping early
+// once a pixel's relative error is below the threshold.
+#[derive(Clone, Copy, Debug)]
+struct Samples {
+    start: usize,
+    end: usize,
+    batch: usize,
+    threshold: Option<f32>,
+}
+
+// The running mean and variance of the brightness of a pixel's samples,
+// using Welford's method.
+#[derive(Clone, Copy, Debug, Default)]
+struct PixelStats {
+    count: u32,
+    mean: f32,
+    squared_deviations: f32,
+}
+
+impl PixelStats {
+    fn add(&mut self, brightness: f32) {
+        self.count += 1;
+        let delta = brightness - self.mean;
+        self.mean += delta / self.count as f32;
+        self.squared_deviations += delta * (brightness - self.mean);
+    }
+
+    fn converged(&self, threshold: f32) -> bool {
+        if self.count < 2 {
+            return false;
+        }
+
+        let n = self.count as f32;
+        let variance = self.squared_deviations / (n - 1.0);
+        let error = (variance / n).sqrt();
+
+        error <= threshold * self.mean.max(MIN_BRIGHTNESS)
+    }
+}
+
+// Models with bounding boxes go in the BVH; the rest (like planes) are
+// tested one by one. Models to sample as area lights are listed in order
+// (the background, if it gives off light, comes after them.)
+// Built on the first hit after the models change.
+struct Accelerator {
+    bvh: Bvh,
+    unbounded: Vec<usize>,
+    area_lights: Vec<usize>,
 }
 
 impl Scene {
+    pub fn builder(camera: Box<dyn Camera>) -> SceneBuilder {
+        SceneBuilder::new(camera)
+    }
+
     pub fn new(camera: Box<dyn Camera>) -> Self {
         Self {
             camera,
             models: vec![],
+            lights: vec![],
+            background: Box::new(Sky::default()),
+            max_depth: 50,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            sampler: Box::new(SobolSampler::new()),
+            filter: Box::new(BoxFilter::new(0.5)),
+            adaptive: None,
+            accelerator: OnceLock::new(),
         }
     }
 
     pub fn add(&mut self, model: Box<dyn Model>) {
         self.models.push(model);
+        self.accelerator = OnceLock::new();
+    }
+
+    pub fn add_light(&mut self, light: Box<dyn Light>) {
+        self.lights.push(light);
+    }
+
+    pub fn set_sky(&mut self, sky: Sky) {
+        self.set_background(Box::new(sky));
+    }
+
+    // What rays that escape the scene see, and the light from all around
+    // it. By default, the sky.
+    pub fn set_background(&mut self, background: Box<dyn Background>) {
+        self.background = background;
+    }
+
+    // The number of bounces after which a path is cut off and contributes
+    // nothing.
+    pub fn set_max_depth(&mut self, max_depth: i32) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    // Each tile is rendered with a clone of this sampler. Samplers with the
+    // same seed render the same image, whatever the number of threads.
+    pub fn set_sampler(&mut self, sampler: Box<dyn Sampler>) {
+        self.sampler = sampler;
+    }
+
+    // The filter that `render` spreads samples over the image with.
+    pub fn set_filter(&mut self, filter: Box<dyn Filter>) {
+        self.filter = filter;
+    }
+
+    // With adaptive sampling, the camera's samples per pixel are the least
+    // each pixel takes. None to take exactly that many everywhere.
+    pub fn set_adaptive_sampling(&mut self,
+                                 adaptive: Option<AdaptiveSampling>)
+    {
+        self.adaptive = adaptive;
+    }
+
+    pub fn render(&self) -> Image {
+        let mut film = self.film();
+        self.render_to(&mut film);
+
+        film.image()
     }
 
-    pub fn render(&self) {
-        println!("P3");
-        println!(
-            "{} {}",
+    // An empty film the size of the camera's image, with the scene's filter.
+    pub fn film(&self) -> Film {
+        Film::with_filter(
             self.camera.get_image_width(),
-            self.camera.get_image_height()
-        );
-        println!("255");
+            self.camera.get_image_height(),
+            self.filter.clone_box(),
+        )
+    }
 
-        let mut rng = thread_rng();
+    // Renders into the film, adding to whatever samples it already has.
+    pub fn render_to(&self, film: &mut Film) {
+        self.render_to_with(film, |_| {});
+    }
 
-        for y in (0..self.camera.get_image_height()).rev() {
-            for x in 0..self.camera.get_image_width() {
-                let mut pixel_color = Color::black();
-                for ray in self.camera.rays(x, y) {
-                    pixel_color += self.color(ray, 0, &mut rng);
-                }
-                pixel_color /= self.camera.get_samples_per_pixel() as f32;
+    // Renders into the film like render_to(), but lets `after_tile` see the
+    // film whenever more tiles' samples have been merged into it, say to
+    // save it. Tiles are merged in order, once those before them are done.
+    pub fn render_to_with<F>(&self, film: &mut Film, mut after_tile: F)
+        where F: FnMut(&Film)
+    {
+        let batch = self.camera.get_samples_per_pixel();
 
-                pixel_color.r = pixel_color.r.sqrt();
-                pixel_color.g = pixel_color.g.sqrt();
-                pixel_color.b = pixel_color.b.sqrt();
+        self.render_samples(film, Samples {
+            start: 0,
+            end: self.adaptive
+                .map_or(batch, |adaptive| adaptive.max_samples.max(batch)),
+            batch,
+            threshold: self.adaptive.map(|adaptive| adaptive.threshold),
+        }, &mut after_tile);
+    }
 
-                let ir = (255.99 * pixel_color.r) as i32;
-                let ig = (255.99 * pixel_color.g) as i32;
-                let ib = (255.99 * pixel_color.b) as i32;
+    // Renders samples first..first + count of every pixel into the film:
+    // one pass of a progressive render. Adaptive sampling isn't used.
+    pub fn render_pass(&self, film: &mut Film, first: usize, count: usize) {
+        self.render_samples(film, Samples {
+            start: first,
+            end: first + count,
+            batch: count,
+            threshold: None,
+        }, &mut |_| {});
+    }
+
+    // Renders the image in passes, each taking `progressive.pass_samples`
+    // more samples of every pixel, until every pixel has the camera's
+    // samples per pixel or the time limit has passed. The time is only
+    // checked between passes, so the last pass may run over. After each
+    // pass, `after_pass` gets to see the film so far, say to save it.
+    pub fn render_progressive<F>(&self,
+                                 film: &mut Film,
+                                 progressive: &Progressive,
+                                 mut after_pass: F)
+        where F: FnMut(&Film, &Progress)
+    {
+        let started = Instant::now();
+        let target = self.camera.get_samples_per_pixel();
+        let mut progress = Progress {
+            passes: 0,
+            samples_per_pixel: 0,
+            elapsed: Duration::from_secs(0),
+        };
+
+        while progress.samples_per_pixel < target {
+            // Every pass but maybe the last is a whole batch, which is how
+            // the sampler sees them.
+            let batch = progressive.pass_samples.max(1);
+            let start = progress.samples_per_pixel;
+            let end = (start + batch).min(target);
+            self.render_samples(film, Samples {
+                start,
+                end,
+                batch,
+                threshold: None,
+            }, &mut |_| {});
 
-                println!("{} {} {}", ir, ig, ib);
+            progress.passes += 1;
+            progress.samples_per_pixel = end;
+            progress.elapsed = started.elapsed();
+            after_pass(film, &progress);
+
+            match progressive.time_limit {
+                Some(limit) if progress.elapsed >= limit => break,
+                _ => {}
             }
         }
-/*
-        let mut rng = thread_rng();
+    }
 
-        for j in (0..self.image_height).rev() {
-            for i in 0..self.image_width {
-                let mut c = Color::black();
-                for _ in 0..self.samples_per_pixel {
-                    let u = (i as f64 + rng.gen::<f64>()) 
-                          / self.image_width as f64;
-                    let v = (j as f64 + rng.gen::<f64>()) 
-                          / self.image_height as f64;
+    // Tiles are handed out to the worker threads one at a time, so threads
+    // that finish early pick up the remaining work. Finished tiles come
+    // back to this thread to be merged into the film.
+    fn render_samples(&self,
+                      film: &mut Film,
+                      samples: Samples,
+                      after_tile: &mut dyn FnMut(&Film))
+    {
+        let tiles = Tile::split(film.width(), film.height(), TILE_SIZE);
+        let threads = self.threads.min(tiles.len()).max(1);
+        let queue = Mutex::new(
+            tiles.iter()
+                .map(|&tile| film.tile(tile))
+                .enumerate()
+                .collect::<Vec<_>>()
+                .into_iter()
+        );
+        let (done, finished) = mpsc::channel();
 
-                    let r = self.camera.get_ray(u, v);
+        thread::scope(|s| {
+            for _ in 0..threads {
+                let done = done.clone();
+                let queue = &queue;
+                s.spawn(move || loop {
+                    let next = queue.lock().unwrap().next();
+                    match next {
+                        Some((i, mut film_tile)) => {
+                            self.render_tile(&mut film_tile, samples);
+                            if done.send((i, film_tile)).is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                });
+            }
+            drop(done);
 
-                    c += self.color(r, 0, &mut rng);
+            // Tiles overlap where the filter spreads samples past their
+            // edges, and floating point sums depend on their order, so merge
+            // them in the same order however the threads shared them out.
+            let mut rendered: Vec<Option<FilmTile>> =
+                tiles.iter().map(|_| None).collect();
+            let mut merged = 0;
+            for (i, film_tile) in finished {
+                rendered[i] = Some(film_tile);
+                let before = merged;
+                while let Some(Some(film_tile)) = rendered.get(merged) {
+                    film.merge_tile(film_tile);
+                    rendered[merged] = None;
+                    merged += 1;
                 }
-                c /= self.samples_per_pixel as f32;
-                c = Color::new(c.r.sqrt(), c.g.sqrt(), c.b.sqrt());
+                if merged > before {
+                    after_tile(film);
+                }
+            }
+            assert_eq!(merged, tiles.len(), "render thread panicked");
+        });
+    }
 
-                let ir = (255.99 * c.r) as i32;
-                let ig = (255.99 * c.g) as i32;
-                let ib = (255.99 * c.b) as i32;
+    fn render_tile(&self, film_tile: &mut FilmTile, samples: Samples) {
+        let tile = film_tile.bounds();
+        let mut sampler = self.sampler.clone_box();
 
-                println!("{} {} {}", ir, ig, ib);
+        for y in tile.y0..tile.y1 {
+            for x in tile.x0..tile.x1 {
+                self.render_pixel(film_tile, &mut *sampler, x, y, samples);
             }
         }
-*/
     }
 
-    pub fn color(&self, ray: Ray, depth: i32, rng: &mut ThreadRng) -> Color {
-        match self.hit(&ray, 0.001, std::f64::MAX) {
-            Some(hr) => {
-                if depth < 50 {
-                    match hr.material.scatter(&ray, &hr) {
-                        Some(sr) => self.color(sr.scattered, depth + 1, rng)
-                                  * sr.attenuation,
-                        None => Color::black(),
+    // Samples the pixel in batches, so that each batch is spread over the
+    // pixel as well as the sampler can. Without adaptive sampling, it takes
+    // every sample in the range.
+    fn render_pixel(&self,
+                    film_tile: &mut FilmTile,
+                    sampler: &mut dyn Sampler,
+                    x: usize,
+                    y: usize,
+                    samples: Samples)
+    {
+        let Samples { start, end, batch, threshold } = samples;
+        let mut stats = PixelStats::default();
+        let mut taken = start;
+
+        while taken < end {
+            for i in taken..(taken + batch).min(end) {
+                sampler.start_pixel_sample(x, y, i, batch);
+                let (dx, dy) = sampler.get_2d();
+                let (fx, fy) = (x as f64 + dx, y as f64 + dy);
+                let color = match self.camera.get_ray(fx, fy, sampler) {
+                    Some(ray) => {
+                        let color = self.color(ray, 0, sampler);
+                        film_tile.add_sample(fx, fy, color);
+                        color
                     }
-                } else {
-                    Color::black()
+                    None => Color::black(),
+                };
+                stats.add(color.luminance());
+            }
+            taken = (taken + batch).min(end);
+
+            match threshold {
+                Some(threshold) if stats.converged(threshold) => break,
+                _ => {}
+            }
+        }
+    }
+
+    // Follows a path from the ray, bounce by bounce. Where it scatters off
+    // a surface with a BSDF that isn't all delta lobes, one of the area
+    // lights (or the background) is also sampled directly with a shadow ray,
+    // as is every punctual light. Light that could be found either way is
+    // weighted by multiple importance sampling, towards whichever way finds
+    // it with less noise.
+    pub fn color(&self, ray: Ray, depth: i32, sampler: &mut dyn Sampler)
+        -> Color
+    {
+        let mut ray = ray;
+        let mut depth = depth;
+        let mut color = Color::black();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        // The density with which the last bounce picked the ray's direction,
+        // or None for a delta lobe (or if there was no bounce.)
+        let mut scatter_pdf = None;
+
+        loop {
+            let (model, hr) = match self.hit(&ray, 0.001, f64::MAX) {
+                Some(hit) => hit,
+                None => {
+                    let direction = ray.direction.hat();
+                    let weight = match scatter_pdf {
+                        Some(pdf) if self.background.is_light() => {
+                            let background_pdf = self.background
+                                .pdf(direction) / self.light_count() as f64;
+                            power_heuristic(pdf, background_pdf)
+                        }
+                        _ => 1.0,
+                    };
+                    color += throughput
+                        * self.background.radiance(direction)
+                        * weight as f32;
+                    break;
                 }
+            };
+
+            let emitted = hr.material.emitted(&ray, &hr);
+            if emitted != Color::black() {
+                let weight = match scatter_pdf {
+                    Some(pdf) => power_heuristic(
+                        pdf,
+                        self.light_pdf(model, ray.origin, &hr),
+                    ),
+                    None => 1.0,
+                };
+                color += throughput * emitted * weight as f32;
+            }
+            if depth >= self.max_depth {
+                break;
+            }
+
+            let wo = -ray.direction.hat();
+            color += throughput * self.sample_lights(wo, &hr, sampler);
+            color += throughput * self.punctual_light(wo, &hr);
+
+            match hr.material.sample(&hr, wo, sampler) {
+                Some(sample) => {
+                    let cosine = sample.wi.dot(hr.normal).abs();
+                    throughput = throughput
+                        * (sample.f * (cosine / sample.pdf));
+                    scatter_pdf = if sample.delta {
+                        None
+                    } else {
+                        Some(sample.pdf)
+                    };
+                    ray = Ray::new(hr.hit_point, sample.wi);
+                    depth += 1;
+                }
+                None => break,
+            }
+        }
+
+        color
+    }
+
+    // The light reaching the hit straight from a point on one of the
+    // area lights, or from the background, picked uniformly, and scattered
+    // back along the ray. It takes the same three dimensions of the sampler
+    // whatever happens, so that later ones line up from sample to sample.
+    fn sample_lights(&self,
+                     wo: Vector,
+                     hr: &HitResult,
+                     sampler: &mut dyn Sampler)
+        -> Color
+    {
+        let n = self.light_count();
+        if n == 0 {
+            return Color::black();
+        }
+
+        let pick = sampler.get_1d();
+        let u = sampler.get_2d();
+        let lights = &self.accelerator().area_lights;
+        let light = match lights.get(((pick * n as f64) as usize).min(n - 1)) {
+            Some(&light) => light,
+            None => return self.sample_background(wo, hr, u),
+        };
+        let sample = match self.models[light].sample_light(hr.hit_point, u) {
+            Some(sample) => sample,
+            None => return Color::black(),
+        };
+
+        let to_light = sample.point - hr.hit_point;
+        let distance = to_light.norm();
+        let direction = to_light / distance;
+        let f = hr.material.eval(hr, wo, direction);
+        if f == Vector::zero() {
+            return Color::black();
+        }
+        let scattered = f * direction.dot(hr.normal).abs();
+        let scatter_pdf = hr.material.pdf(hr, wo, direction);
+
+        // The shadow ray has to reach the point on the light before it
+        // hits anything else.
+        let shadow = Ray::new(hr.hit_point, direction);
+        let tolerance = 1e-4 * distance;
+        match self.hit(&shadow, 0.001, distance + tolerance) {
+            Some((model, light_hr))
+                if model == light
+                    && (light_hr.t - distance).abs() <= tolerance =>
+            {
+                let light_pdf = sample.pdf / n as f64;
+                let weight = power_heuristic(light_pdf, scatter_pdf);
+                let emitted = light_hr.material.emitted(&shadow, &light_hr);
+
+                emitted * scattered * (weight / light_pdf) as f32
             }
-            None => {
-                let unit_direction = ray.direction.hat();
-                let t = 0.5 * (unit_direction.y as f32 + 1.0);
+            _ => Color::black(),
+        }
+    }
+
+    // The light reaching the hit from the background along a direction it
+    // picks, if nothing is in the way, for `sample_lights`.
+    fn sample_background(&self, wo: Vector, hr: &HitResult, u: (f64, f64))
+        -> Color
+    {
+        let (direction, pdf) = match self.background.sample(u) {
+            Some(sample) => sample,
+            None => return Color::black(),
+        };
+        let f = hr.material.eval(hr, wo, direction);
+        if f == Vector::zero() {
+            return Color::black();
+        }
+
+        let shadow = Ray::new(hr.hit_point, direction);
+        if self.hit(&shadow, 0.001, f64::MAX).is_some() {
+            return Color::black();
+        }
 
-                Color::blend(
-                    t,
-                    Color::new(1.0, 1.0, 1.0),
-                    Color::new(0.5, 0.7, 1.0),
-                )
+        let scattered = f * direction.dot(hr.normal).abs();
+        let scatter_pdf = hr.material.pdf(hr, wo, direction);
+        let light_pdf = pdf / self.light_count() as f64;
+        let weight = power_heuristic(light_pdf, scatter_pdf);
+
+        self.background.radiance(direction)
+            * scattered
+            * (weight / light_pdf) as f32
+    }
+
+    // The light reaching the hit from every punctual light with nothing in
+    // the way, scattered back along the ray. Scattered rays never find
+    // punctual lights, so there's nothing to weigh it against.
+    fn punctual_light(&self, wo: Vector, hr: &HitResult) -> Color {
+        let mut color = Color::black();
+
+        for light in &self.lights {
+            let il = match light.illuminate(hr.hit_point) {
+                Some(il) => il,
+                None => continue,
+            };
+            let f = hr.material.eval(hr, wo, il.direction);
+            if f == Vector::zero() {
+                continue;
             }
+
+            let shadow = Ray::new(hr.hit_point, il.direction);
+            let tmax = il.distance * (1.0 - 1e-4);
+            if self.hit(&shadow, 0.001, tmax).is_none() {
+                let cosine = il.direction.dot(hr.normal).abs();
+                color += il.irradiance * (f * cosine);
+            }
+        }
+
+        color
+    }
+
+    // The density by solid angle with which `sample_lights`, lighting
+    // `from`, picks the hit on the model.
+    fn light_pdf(&self, model: usize, from: Point, hr: &HitResult) -> f64 {
+        let lights = &self.accelerator().area_lights;
+
+        if lights.binary_search(&model).is_ok() {
+            self.models[model].light_pdf(from, hr) / self.light_count() as f64
+        } else {
+            0.0
         }
     }
 
-    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64) -> Option<HitResult> {
+    // The number of lights `sample_lights` picks from.
+    fn light_count(&self) -> usize {
+        self.accelerator().area_lights.len()
+            + self.background.is_light() as usize
+    }
+
+    // The nearest hit along the ray, and which model it's on.
+    fn hit(&self, ray: &Ray, tmin: f64, tmax: f64)
+        -> Option<(usize, HitResult<'_>)>
+    {
+        let accelerator = self.accelerator();
         let mut closest_so_far = tmax;
         let mut rc = None;
 
-        for model in &self.models {
-            match model.hit(ray, tmin, closest_so_far) {
-                Some(hr) => {
-                    closest_so_far = hr.t;
-                    rc = Some(hr);
+        for &i in &accelerator.unbounded {
+            if let Some(hr) = self.models[i].hit(ray, tmin, closest_so_far) {
+                closest_so_far = hr.t;
+                rc = Some((i, hr));
+            }
+        }
+
+        // The BVH only passes on the nearest hit, which is the last one the
+        // closure finds.
+        let mut nearest = 0;
+        accelerator.bvh
+            .hit(ray, tmin, closest_so_far, |i, tmin, tmax| {
+                let hr = self.models[i].hit(ray, tmin, tmax);
+                if hr.is_some() {
+                    nearest = i;
                 }
-                None => {}
+                hr
+            })
+            .map(|hr| (nearest, hr))
+            .or(rc)
+    }
+
+    fn accelerator(&self) -> &Accelerator {
+        self.accelerator.get_or_init(|| self.build_accelerator())
+    }
+
+    fn build_accelerator(&self) -> Accelerator {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+
+        for (i, model) in self.models.iter().enumerate() {
+            match model.bounding_box() {
+                Some(bounds) => bounded.push((i, bounds)),
+                None => unbounded.push(i),
             }
         }
 
-        rc
+        Accelerator {
+            bvh: Bvh::new(bounded),
+            unbounded,
+            area_lights: (0..self.models.len())
+                .filter(|&i| self.models[i].is_light())
+                .collect(),
+        }
+    }
+}
+
+// Veach's power heuristic, with an exponent of two: how much to count a
+// sample picked with density `f` that could also have been picked another
+// way with density `g`.
+fn power_heuristic(f: f64, g: f64) -> f64 {
+    let (f, g) = (f * f, g * g);
+
+    if f + g > 0.0 { f / (f + g) } else { 0.0 }
+}
+
+// Puts a scene together in one expression:
+//
+//     let scene = Scene::builder(camera)
+//         .model(sphere)
+//         .max_depth(8)
+//         .build();
+pub struct SceneBuilder {
+    scene: Scene,
+}
+
+impl SceneBuilder {
+    pub fn new(camera: Box<dyn Camera>) -> Self {
+        Self { scene: Scene::new(camera) }
+    }
+
+    pub fn model(mut self, model: Box<dyn Model>) -> Self {
+        self.scene.add(model);
+        self
+    }
+
+    pub fn models<I>(mut self, models: I) -> Self
+        where I: IntoIterator<Item=Box<dyn Model>>
+    {
+        for model in models {
+            self.scene.add(model);
+        }
+        self
+    }
+
+    pub fn light(mut self, light: Box<dyn Light>) -> Self {
+        self.scene.add_light(light);
+        self
+    }
+
+    pub fn lights<I>(mut self, lights: I) -> Self
+        where I: IntoIterator<Item=Box<dyn Light>>
+    {
+        for light in lights {
+            self.scene.add_light(light);
+        }
+        self
+    }
+
+    pub fn sky(mut self, sky: Sky) -> Self {
+        self.scene.set_sky(sky);
+        self
+    }
+
+    pub fn background(mut self, background: Box<dyn Background>) -> Self {
+        self.scene.set_background(background);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: i32) -> Self {
+        self.scene.set_max_depth(max_depth);
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.scene.set_threads(threads);
+        self
+    }
+
+    pub fn sampler(mut self, sampler: Box<dyn Sampler>) -> Self {
+        self.scene.set_sampler(sampler);
+        self
+    }
+
+    pub fn filter(mut self, filter: Box<dyn Filter>) -> Self {
+        self.scene.set_filter(filter);
+        self
+    }
+
+    pub fn adaptive_sampling(mut self, adaptive: AdaptiveSampling) -> Self {
+        self.scene.set_adaptive_sampling(Some(adaptive));
+        self
+    }
+
+    pub fn build(self) -> Scene {
+        self.scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use crate::cameras::OrthographicCamera;
+    use crate::materials::Lambertian;
+    use crate::math::{ Point, Vector };
+    use crate::models::{ Plane, Sphere };
+
+    use super::*;
+
+    #[test]
+    fn pixel_stats() {
+        let mut stats = PixelStats::default();
+        for &x in &[1.0, 2.0, 3.0, 4.0] {
+            stats.add(x);
+        }
+
+        // The mean is 2.5 and the variance 5/3, so the standard error is
+        // sqrt(5/12), about 0.645, or 26% of the mean.
+        assert_eq!(stats.mean, 2.5);
+        assert!((stats.squared_deviations - 5.0).abs() < 1e-6);
+        assert!(stats.converged(0.26));
+        assert!(!stats.converged(0.25));
+
+        let mut dark = PixelStats::default();
+        dark.add(0.0);
+        assert!(!dark.converged(0.1));
+        dark.add(0.0);
+        assert!(dark.converged(0.1));
+    }
+
+    #[test]
+    fn hit_matches_linear_scan() {
+        let mut rng = StdRng::from_seed([3; 32]);
+        let mut scene = Scene::new(
+            Box::new(OrthographicCamera::new(1, 1, 1.0, 1)),
+        );
+        let material = || Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5)));
+
+        scene.add(Box::new(Plane::new(
+            Point::new(0.0, -5.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            material(),
+        )));
+        for _ in 0..200 {
+            scene.add(Box::new(Sphere::new(
+                Point::new(
+                    rng.gen_range(-5.0, 5.0),
+                    rng.gen_range(-5.0, 5.0),
+                    rng.gen_range(-5.0, 5.0),
+                ),
+                rng.gen_range(0.1, 0.5),
+                material(),
+            )));
+        }
+
+        for _ in 0..1000 {
+            let ray = Ray::new(
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                ),
+            );
+
+            let mut expected = None;
+            for model in &scene.models {
+                let tmax = expected.unwrap_or(f64::MAX);
+                if let Some(hr) = model.hit(&ray, 0.001, tmax) {
+                    expected = Some(hr.t);
+                }
+            }
+
+            let actual = scene.hit(&ray, 0.001, f64::MAX)
+                .map(|(i, hr)| (i, hr.t));
+            assert_eq!(actual.map(|(_, t)| t), expected);
+            if let Some((i, t)) = actual {
+                assert_eq!(scene.models[i].hit(&ray, 0.001, f64::MAX)
+                               .map(|hr| hr.t), Some(t));
+            }
+        }
     }
 }
@@ -0,0 +1,71 @@
+// tile.rs - Rectangular tiles of an image, for rendering in parallel.
+// Written by quadfault
+// 10/18/26
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Tile {
+    // Splits a `width` x `height` image into tiles no larger than
+    // `size` x `size`, in row-major order.
+    pub fn split(width: usize, height: usize, size: usize) -> Vec<Self> {
+        let mut tiles = vec![];
+
+        for y0 in (0..height).step_by(size) {
+            for x0 in (0..width).step_by(size) {
+                tiles.push(Self {
+                    x0,
+                    y0,
+                    x1: (x0 + size).min(width),
+                    y1: (y0 + size).min(height),
+                });
+            }
+        }
+
+        tiles
+    }
+
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_covers_every_pixel_once() {
+        let (width, height) = (37, 20);
+        let mut covered = vec![0; width * height];
+
+        for tile in Tile::split(width, height, 16) {
+            for y in tile.y0..tile.y1 {
+                for x in tile.x0..tile.x1 {
+                    covered[y * width + x] += 1;
+                }
+            }
+        }
+
+        assert!(covered.iter().all(|&n| n == 1));
+    }
+
+    #[test]
+    fn split_clips_edge_tiles() {
+        let tiles = Tile::split(20, 10, 16);
+
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[1], Tile { x0: 16, y0: 0, x1: 20, y1: 10 });
+        assert_eq!(tiles[1].width(), 4);
+        assert_eq!(tiles[1].height(), 10);
+    }
+}
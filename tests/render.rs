@@ -0,0 +1,354 @@
+// tests/render.rs - Rendering small scenes through the library.
+// Written by quadfault
+// 10/18/26
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use rt::backgrounds::EnvironmentMap;
+use rt::cameras::{ EquirectangularCamera, LookAt, OrthographicCamera };
+use rt::filters::MitchellFilter;
+use rt::image::{ Image, ImageFormat };
+use rt::lights::PointLight;
+use rt::materials::{ DiffuseLight, Lambertian, Metal };
+use rt::math::{ Color, Point, Vector };
+use rt::models::{ Plane, Sphere };
+use rt::samplers::IndependentSampler;
+use rt::{ AdaptiveSampling, Progressive, Scene, Sky };
+
+fn camera(width: usize, height: usize) -> Box<OrthographicCamera> {
+    Box::new(OrthographicCamera::new(width, height, 4.0, 4))
+}
+
+fn grey() -> Box<Lambertian> {
+    Box::new(Lambertian::new(Vector::new(0.5, 0.5, 0.5)))
+}
+
+#[test]
+fn empty_scene_shows_the_sky() {
+    let sky = Color::new(0.25, 0.5, 0.75);
+    let image = Scene::builder(camera(8, 6))
+        .sky(Sky { horizon: sky, zenith: sky })
+        .build()
+        .render();
+
+    assert_eq!(image.width(), 8);
+    assert_eq!(image.height(), 6);
+    for pixel in image.pixels() {
+        assert!((pixel.r - sky.r).abs() < 1e-6);
+        assert!((pixel.g - sky.g).abs() < 1e-6);
+        assert!((pixel.b - sky.b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn sphere_darkens_the_middle() {
+    let white = Color::new(1.0, 1.0, 1.0);
+    let image = Scene::builder(camera(16, 16))
+        .sky(Sky { horizon: white, zenith: white })
+        .model(Box::new(Sphere::new(
+            Point::new(0.0, 0.0, -5.0),
+            1.0,
+            grey(),
+        )))
+        .max_depth(4)
+        .threads(3)
+        .build()
+        .render();
+
+    // Rays through the middle bounce off the sphere at least once, losing
+    // half their energy; rays at the corners only ever see the sky.
+    assert!(image.get(8, 8).g < 0.6);
+    assert_eq!(image.get(0, 0), white);
+    assert_eq!(image.get(15, 15), white);
+}
+
+#[test]
+fn thread_count_does_not_change_the_image_size() {
+    for threads in 1..4 {
+        let image = Scene::builder(camera(50, 37))
+            .threads(threads)
+            .build()
+            .render();
+
+        assert_eq!(image.pixels().len(), 50 * 37);
+    }
+}
+
+// Spheres that scatter rays at random, rendered with a filter wide enough
+// that tiles overlap.
+fn noisy_render(seed: u64, threads: usize) -> Image {
+    Scene::builder(camera(70, 40))
+        .model(Box::new(Sphere::new(
+            Point::new(-0.5, 0.0, -5.0),
+            0.8,
+            grey(),
+        )))
+        .model(Box::new(Sphere::new(
+            Point::new(1.0, 0.2, -4.0),
+            0.6,
+            Box::new(Metal::new(Vector::new(0.8, 0.6, 0.2), 0.4)),
+        )))
+        .filter(Box::new(MitchellFilter::new(2.0, 1.0 / 3.0, 1.0 / 3.0)))
+        .sampler(Box::new(IndependentSampler::with_seed(seed)))
+        .threads(threads)
+        .build()
+        .render()
+}
+
+#[test]
+fn seeds_make_renders_repeatable() {
+    let image = noisy_render(7, 1);
+
+    for threads in 2..6 {
+        assert_eq!(noisy_render(7, threads).pixels(), image.pixels());
+    }
+    assert_ne!(noisy_render(8, 3).pixels(), image.pixels());
+}
+
+#[test]
+fn adaptive_sampling_spends_samples_where_the_noise_is() {
+    let scene = Scene::builder(camera(16, 16))
+        .model(Box::new(Sphere::new(
+            Point::new(0.0, 0.0, -5.0),
+            1.0,
+            grey(),
+        )))
+        .adaptive_sampling(AdaptiveSampling {
+            max_samples: 64,
+            threshold: 0.01,
+        })
+        .build();
+    let mut film = scene.film();
+    scene.render_to(&mut film);
+    let counts = film.sample_counts();
+
+    // The sky is smooth enough that one batch of four will do, but light
+    // bouncing off the sphere comes from all over.
+    assert_eq!(counts[0], 4);
+    assert_eq!(counts[8 * 16 + 8], 64);
+    assert!(counts.iter().all(|&n| (4..=64).contains(&n)));
+}
+
+fn sphere_scene(samples_per_pixel: usize) -> Scene {
+    let camera = OrthographicCamera::new(20, 10, 4.0, samples_per_pixel);
+
+    Scene::builder(Box::new(camera))
+        .model(Box::new(Sphere::new(Point::new(0.0, 0.0, -5.0), 1.0, grey())))
+        .build()
+}
+
+#[test]
+fn progressive_renders_take_passes() {
+    let progressive = Progressive { pass_samples: 4, time_limit: None };
+    let scene = sphere_scene(10);
+    let mut film = scene.film();
+    let mut passes = vec![];
+    scene.render_progressive(&mut film, &progressive, |film, progress| {
+        passes.push(progress.samples_per_pixel);
+        assert!(film.sample_counts()
+            .iter()
+            .all(|&n| n as usize == progress.samples_per_pixel));
+    });
+    assert_eq!(passes, [4, 8, 10]);
+
+    // The passes take the same samples as rendering them one by one.
+    let scene = sphere_scene(8);
+    let mut film = scene.film();
+    scene.render_progressive(&mut film, &progressive, |_, _| {});
+    let mut by_hand = scene.film();
+    scene.render_pass(&mut by_hand, 0, 4);
+    scene.render_pass(&mut by_hand, 4, 4);
+    assert_eq!(film.image().pixels(), by_hand.image().pixels());
+}
+
+#[test]
+fn renders_can_be_watched_tile_by_tile() {
+    // 80x40 pixels make three by two tiles of up to 32.
+    let scene = Scene::builder(camera(80, 40))
+        .model(Box::new(Sphere::new(Point::new(0.0, 0.0, -5.0), 1.0, grey())))
+        .threads(3)
+        .build();
+    let mut film = scene.film();
+    let mut sampled = vec![];
+    scene.render_to_with(&mut film, |film| {
+        sampled.push(film.sample_counts().iter().filter(|&&n| n > 0).count());
+    });
+
+    // Each look sees more of the image, and the last sees all of it.
+    assert!(!sampled.is_empty() && sampled.len() <= 6);
+    assert!(sampled.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(sampled.last(), Some(&(80 * 40)));
+
+    let mut whole = scene.film();
+    scene.render_to(&mut whole);
+    assert_eq!(film.image().pixels(), whole.image().pixels());
+}
+
+#[test]
+fn progressive_renders_stop_on_time() {
+    let scene = Scene::builder(camera(8, 8)).build();
+    let progressive = Progressive {
+        pass_samples: 1,
+        time_limit: Some(Duration::from_nanos(1)),
+    };
+    let mut film = scene.film();
+    let mut passes = 0;
+    scene.render_progressive(&mut film, &progressive, |_, _| passes += 1);
+
+    assert_eq!(passes, 1);
+    assert!(film.sample_counts().iter().all(|&n| n == 1));
+}
+
+#[test]
+fn small_lights_are_sampled_directly() {
+    // A grey floor a unit below the camera, lit by a small, bright sphere
+    // off to the side, and nothing else.
+    let black = Color::black();
+    let image = Scene::builder(Box::new(
+            OrthographicCamera::new(1, 1, 0.001, 16)
+        ))
+        .sky(Sky { horizon: black, zenith: black })
+        .model(Box::new(Plane::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 0.0, 1.0),
+            grey(),
+        )))
+        .model(Box::new(Sphere::new(
+            Point::new(1.0, 0.0, 0.0),
+            0.05,
+            Box::new(DiffuseLight::new(Color::new(100.0, 100.0, 100.0))),
+        )))
+        .max_depth(1)
+        .build()
+        .render();
+
+    // The sphere is sqrt(2) away, 45 degrees off the floor's normal, so the
+    // floor reflects albedo * emit * (radius / distance)^2 * cos(45).
+    let expected = 0.5 * 100.0 * (0.05 * 0.05 / 2.0) * 0.5f32.sqrt();
+    let pixel = image.get(0, 0);
+    assert!((pixel.g - expected).abs() < 0.02 * expected, "got {:?}", pixel);
+}
+
+// A grey floor a unit below the camera, lit by a point light off to the
+// side, with a small sphere perhaps in the way.
+fn point_lit_floor(blocked: bool) -> Color {
+    let black = Color::black();
+    let mut builder = Scene::builder(Box::new(
+            OrthographicCamera::new(1, 1, 0.001, 4)
+        ))
+        .sky(Sky { horizon: black, zenith: black })
+        .model(Box::new(Plane::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 0.0, 1.0),
+            grey(),
+        )))
+        .light(Box::new(PointLight::new(
+            Point::new(1.0, 0.0, 0.0),
+            Color::new(10.0, 10.0, 10.0),
+        )))
+        .max_depth(1);
+    if blocked {
+        builder = builder.model(Box::new(Sphere::new(
+            Point::new(0.5, 0.0, -0.5),
+            0.1,
+            grey(),
+        )));
+    }
+
+    builder.build().render().get(0, 0)
+}
+
+#[test]
+fn point_lights_cast_shadows() {
+    // The light is sqrt(2) away, 45 degrees off the floor's normal, so the
+    // floor reflects albedo / pi * intensity / distance^2 * cos(45).
+    let expected = 0.5 / std::f32::consts::PI * 10.0 / 2.0 * 0.5f32.sqrt();
+    let lit = point_lit_floor(false);
+    assert!((lit.g - expected).abs() < 1e-4 * expected, "got {:?}", lit);
+
+    assert_eq!(point_lit_floor(true), Color::black());
+}
+
+#[test]
+fn equirectangular_cameras_see_environment_maps_as_they_are() {
+    // Every pixel a different colour.
+    let (width, height) = (16, 8);
+    let pixels = (0..width * height)
+        .map(|i| Color::new(i as f32, 1.0, (i % width) as f32))
+        .collect();
+    let map = Image::new(width, height, pixels);
+    let view = LookAt::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(0.0, 0.0, -1.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let image = Scene::builder(Box::new(
+            EquirectangularCamera::new(width, height, view, 4)
+        ))
+        .background(Box::new(EnvironmentMap::new(map.clone(), 0.0, 0.5)))
+        .build()
+        .render();
+
+    for (pixel, expected) in image.pixels().iter().zip(map.pixels()) {
+        assert!((pixel.r - 0.5 * expected.r).abs() < 1e-4);
+        assert!((pixel.b - 0.5 * expected.b).abs() < 1e-4);
+    }
+}
+
+// A grey floor a unit below the camera (facing +Z), under an environment
+// map that is black but for one bright pixel just off +Z, turned
+// `rotation` degrees.
+fn sun_lit_floor(rotation: f64) -> Color {
+    let (width, height) = (64, 32);
+    let mut pixels = vec![Color::black(); width * height];
+    pixels[12 * width] = Color::new(1000.0, 1000.0, 1000.0);
+    let map = EnvironmentMap::new(Image::new(width, height, pixels),
+                                  rotation,
+                                  1.0);
+
+    Scene::builder(Box::new(OrthographicCamera::new(1, 1, 0.001, 64)))
+        .background(Box::new(map))
+        .model(Box::new(Plane::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 0.0, 1.0),
+            grey(),
+        )))
+        .max_depth(1)
+        .build()
+        .render()
+        .get(0, 0)
+}
+
+#[test]
+fn environment_maps_are_sampled_directly() {
+    // The pixel covers longitudes 0 to 2pi/64 from +Z and colatitudes
+    // 12pi/32 to 13pi/32, so the irradiance it gives the floor is
+    // 1000 * sin(2pi/64) * the integral of sin^2 over those colatitudes,
+    // and the floor reflects albedo / pi of that.
+    let sin_squared = |theta: f32| theta / 2.0 - (2.0 * theta).sin() / 4.0;
+    let irradiance = 1000.0 * (2.0 * PI / 64.0).sin()
+        * (sin_squared(13.0 * PI / 32.0) - sin_squared(12.0 * PI / 32.0));
+    let expected = 0.5 / PI * irradiance;
+
+    let pixel = sun_lit_floor(0.0);
+    assert!((pixel.g - expected).abs() < 0.02 * expected, "got {:?}", pixel);
+
+    // Turned half way round, the bright pixel is below the floor.
+    assert_eq!(sun_lit_floor(180.0), Color::black());
+}
+
+#[test]
+fn images_can_be_written() {
+    let image = Scene::builder(camera(3, 2)).build().render();
+    let mut ppm = vec![];
+    ImageFormat::Ppm.writer().write(&image, &mut ppm).unwrap();
+
+    let ppm = String::from_utf8(ppm).unwrap();
+    let mut lines = ppm.lines();
+
+    assert_eq!(lines.next(), Some("P3"));
+    assert_eq!(lines.next(), Some("3 2"));
+    assert_eq!(lines.next(), Some("255"));
+    assert_eq!(lines.count(), 3 * 2);
+}